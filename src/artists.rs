@@ -0,0 +1,148 @@
+use crate::matcher::Matcher;
+use crate::normalize::{fold, fold_for_matching};
+
+const ARTIST_SEPARATORS: [&str; 13] = [
+    " featuring ",
+    " feat. ",
+    " feat ",
+    " ft. ",
+    " ft ",
+    " vs. ",
+    " vs ",
+    " with ",
+    " & ",
+    " x ",
+    ", ",
+    " / ",
+    "; ",
+];
+
+const FEATURE_MARKERS: [&str; 5] = ["featuring ", "feat. ", "feat ", "ft. ", "ft "];
+
+// expects folded, lowercase text
+fn split_folded(artists: &str) -> Vec<String> {
+    let mut artists = artists.to_owned();
+    for separator in ARTIST_SEPARATORS.iter() {
+        artists = artists.replace(separator, "\n");
+    }
+    artists
+        .split('\n')
+        .map(fold_for_matching)
+        .filter(|artist| !artist.is_empty())
+        .collect()
+}
+
+// splits `A feat. B & C` into the names of all credited artists
+pub fn split_artists(artists: &str) -> Vec<String> {
+    split_folded(&fold(artists).to_lowercase())
+}
+
+// separates featured artists from the title, e.g. `Title (feat. B)` or `Title ft. B`
+pub fn split_featured(title: &str) -> (String, Vec<String>) {
+    let mut title = fold(title).to_lowercase();
+    let mut featured = Vec::new();
+
+    let mut search_from = 0;
+    while let Some(start) = title[search_from..].find(['(', '[']) {
+        let start = search_from + start;
+        let close = if title[start..].starts_with('(') {
+            ')'
+        } else {
+            ']'
+        };
+        let end = title[start..]
+            .find(close)
+            .map_or(title.len(), |index| start + index + 1);
+        let content = title[start + 1..end].trim_end_matches(close).trim();
+        // `with` only introduces an artist within brackets, `Dance With Me` is a title
+        let artists = FEATURE_MARKERS
+            .iter()
+            .chain(["with "].iter())
+            .find_map(|marker| content.strip_prefix(marker));
+        match artists {
+            Some(artists) => {
+                featured.extend(split_folded(artists));
+                title.replace_range(start..end, "");
+                search_from = start;
+            }
+            None => search_from = end,
+        }
+    }
+
+    for marker in FEATURE_MARKERS.iter() {
+        if let Some(index) = title.find(&format!(" {}", marker)) {
+            featured.extend(split_folded(&title[index + marker.len() + 1..]));
+            title.truncate(index);
+            break;
+        }
+    }
+
+    (fold_for_matching(&title), featured)
+}
+
+// every artist is paired with its most similar counterpart, so the order doesn't matter
+pub fn artist_set_similarity(a: &[String], b: &[String], matcher: &dyn Matcher) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let best = |artist: &String, others: &[String]| {
+        others
+            .iter()
+            .map(|other| matcher.similarity(artist, other))
+            .fold(0.0, f64::max)
+    };
+    let total = a.iter().map(|artist| best(artist, b)).sum::<f64>()
+        + b.iter().map(|artist| best(artist, a)).sum::<f64>();
+    total / (a.len() + b.len()) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matcher::MatchSettings;
+    use crate::playlist_types::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn split() {
+        assert_eq!(vec!["a", "b", "c"], split_artists("A feat. B & C"));
+        assert_eq!(vec!["a", "b"], split_artists("A x B"));
+        assert_eq!(vec!["malcolm x"], split_artists("Malcolm X"));
+        assert_eq!(
+            (
+                "title remastered".to_string(),
+                vec!["b".to_string(), "c".to_string()]
+            ),
+            split_featured("Title (feat. B & C) [Remastered]")
+        );
+        assert_eq!(
+            ("title".to_string(), vec!["b".to_string()]),
+            split_featured("Title ft. B")
+        );
+        assert_eq!(
+            ("dance with me".to_string(), Vec::<String>::new()),
+            split_featured("Dance With Me")
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn featured_artists() {
+        let in_track = Track {
+            artist: Some("A".to_string()),
+            title: Some("Song (feat. B)".to_string()),
+            duration: Some(200_000),
+            ..Default::default()
+        };
+        let out_track = Track {
+            artist: Some("B, A".to_string()),
+            artists: vec!["B".to_string(), "A".to_string()],
+            title: Some("Song".to_string()),
+            duration: Some(200_000),
+            ..Default::default()
+        };
+
+        let similarity = in_track.similarity(&out_track, &MatchSettings::default());
+        assert!((similarity.artist.unwrap() - 1.0).abs() < 1e-9);
+        assert!((similarity.title.unwrap() - 1.0).abs() < 1e-9);
+    }
+}
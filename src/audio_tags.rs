@@ -0,0 +1,843 @@
+use crate::m3u::non_empty;
+use crate::playlist_types::*;
+use crate::text_encoding::decode_text;
+
+use std::convert::{TryFrom, TryInto};
+
+// only the beginning and the end of an audio file are read
+pub const HEAD_SIZE: u64 = 512 * 1024;
+pub const TAIL_SIZE: u64 = 512 * 1024;
+
+pub const AUDIO_EXTENSIONS: [&str; 9] = [
+    "mp3", "flac", "ogg", "oga", "opus", "m4a", "m4b", "mp4", "aac",
+];
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AudioTags {
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub track_number: Option<i32>,
+    pub duration: Option<i32>,
+    pub isrc: Option<String>,
+}
+
+impl AudioTags {
+    fn merge(self, other: AudioTags) -> AudioTags {
+        AudioTags {
+            title: self.title.or(other.title),
+            artist: self.artist.or(other.artist),
+            album: self.album.or(other.album),
+            track_number: self.track_number.or(other.track_number),
+            duration: self.duration.or(other.duration),
+            isrc: self.isrc.or(other.isrc),
+        }
+    }
+
+    pub fn into_track(self, location: String) -> Track {
+        Track {
+            location: Some(location),
+            title: self.title,
+            artist: self.artist,
+            album: self.album,
+            track_number: self.track_number,
+            duration: self.duration,
+            isrc: self.isrc,
+            ..Default::default()
+        }
+    }
+}
+
+pub fn is_audio_file(file_name: &str) -> bool {
+    let file_name = file_name.to_lowercase();
+    let extension = file_name.rsplit('.').next().unwrap_or_default();
+    AUDIO_EXTENSIONS.contains(&extension)
+}
+
+// `head` holds the first bytes of the file, `tail` the last ones
+pub fn read_audio_tags(head: &[u8], tail: &[u8], file_size: u64) -> AudioTags {
+    let tags = if head.starts_with(b"fLaC") {
+        read_flac(head)
+    } else if head.starts_with(b"OggS") {
+        read_ogg(head, tail)
+    } else if head.get(4..8) == Some(b"ftyp") {
+        read_mp4(head, tail, file_size)
+    } else {
+        read_mpeg(head, tail, file_size)
+    };
+    tags.merge(read_id3v1(tail))
+}
+
+fn u16_be(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(
+        data.get(offset..offset.checked_add(2)?)?.try_into().ok()?,
+    ))
+}
+
+fn u32_be(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(
+        data.get(offset..offset.checked_add(4)?)?.try_into().ok()?,
+    ))
+}
+
+fn u64_be(data: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_be_bytes(
+        data.get(offset..offset.checked_add(8)?)?.try_into().ok()?,
+    ))
+}
+
+fn u32_le(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        data.get(offset..offset.checked_add(4)?)?.try_into().ok()?,
+    ))
+}
+
+fn syncsafe(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset.checked_add(4)?)?;
+    Some(
+        bytes
+            .iter()
+            .fold(0, |size, byte| (size << 7) | u32::from(byte & 0x7F)),
+    )
+}
+
+// parses `3` or `3/12`
+fn parse_track_number(value: &str) -> Option<i32> {
+    value.split('/').next()?.trim().parse().ok()
+}
+
+// ID3v2 followed by MPEG audio frames
+
+fn read_mpeg(head: &[u8], tail: &[u8], file_size: u64) -> AudioTags {
+    let (mut tags, audio_start) = if head.starts_with(b"ID3") {
+        read_id3v2(head)
+    } else {
+        (AudioTags::default(), 0)
+    };
+    if tags.duration.is_none() {
+        let audio_end = if tail.len() >= 128 && tail[tail.len() - 128..].starts_with(b"TAG") {
+            file_size.saturating_sub(128)
+        } else {
+            file_size
+        };
+        tags.duration = mpeg_duration(head, audio_start, audio_end);
+    }
+    tags
+}
+
+fn read_id3v2(head: &[u8]) -> (AudioTags, usize) {
+    let mut tags = AudioTags::default();
+    let version = head.get(3).copied().unwrap_or_default();
+    let flags = head.get(5).copied().unwrap_or_default();
+    let tag_size = syncsafe(head, 6).unwrap_or_default() as usize;
+    let tag_end = 10 + tag_size + if flags & 0x10 != 0 { 10 } else { 0 };
+
+    let mut data = head[..head.len().min(10 + tag_size)].to_vec();
+    if flags & 0x80 != 0 && version < 4 {
+        // undo the unsynchronisation of the whole tag
+        let mut index = 10;
+        while index + 1 < data.len() {
+            if data[index] == 0xFF && data[index + 1] == 0x00 {
+                data.remove(index + 1);
+            }
+            index += 1;
+        }
+    }
+
+    // the sizes are taken from the file, so a corrupt one must not overflow the offsets
+    let mut offset = 10usize;
+    if flags & 0x40 != 0 {
+        let extended_size = match version {
+            3 => (u32_be(&data, 10).unwrap_or_default() as usize).checked_add(4),
+            4 => Some(syncsafe(&data, 10).unwrap_or_default() as usize),
+            _ => Some(0),
+        };
+        offset = match extended_size.and_then(|size| offset.checked_add(size)) {
+            Some(offset) => offset,
+            None => return (tags, tag_end),
+        };
+    }
+
+    let (id_length, header_length) = if version == 2 { (3, 6) } else { (4, 10) };
+    while let Some(frame_start) = offset
+        .checked_add(header_length)
+        .filter(|frame_start| *frame_start <= data.len())
+    {
+        let id = &data[offset..offset + id_length];
+        if id[0] == 0 {
+            // padding
+            break;
+        }
+        let frame_size = match version {
+            2 => data[offset + 3..offset + 6]
+                .iter()
+                .fold(0, |size, byte| (size << 8) | usize::from(*byte)),
+            3 => u32_be(&data, offset + 4).unwrap_or_default() as usize,
+            _ => syncsafe(&data, offset + 4).unwrap_or_default() as usize,
+        };
+        let frame = match frame_start
+            .checked_add(frame_size)
+            .and_then(|frame_end| data.get(frame_start..frame_end))
+        {
+            Some(frame) => frame,
+            None => break,
+        };
+        let text = || decode_id3_text(frame).and_then(|text| non_empty(&text));
+        match id {
+            b"TIT2" | b"TT2" => tags.title = text(),
+            b"TPE1" | b"TP1" => tags.artist = text(),
+            b"TALB" | b"TAL" => tags.album = text(),
+            b"TRCK" | b"TRK" => {
+                tags.track_number = text().and_then(|text| parse_track_number(&text))
+            }
+            b"TLEN" | b"TLE" => {
+                tags.duration = text()
+                    .and_then(|text| text.parse().ok())
+                    .filter(|duration| *duration > 0)
+            }
+            b"TSRC" | b"TRC" => tags.isrc = text(),
+            _ => {}
+        }
+        offset = frame_start + frame_size;
+    }
+
+    (tags, tag_end)
+}
+
+fn decode_id3_text(frame: &[u8]) -> Option<String> {
+    let (encoding, content) = frame.split_first()?;
+    let text = match encoding {
+        // UTF-16 with BOM
+        1 => decode_text(content),
+        // UTF-16BE without BOM
+        2 => {
+            let mut bom_content = b"\xFE\xFF".to_vec();
+            bom_content.extend_from_slice(content);
+            decode_text(&bom_content)
+        }
+        3 => String::from_utf8_lossy(content).into_owned(),
+        _ => content.iter().copied().map(char::from).collect(),
+    };
+    // multiple values are separated by null characters
+    text.split('\0').next().map(ToOwned::to_owned)
+}
+
+fn mpeg_duration(head: &[u8], audio_start: usize, audio_end: u64) -> Option<i32> {
+    let frame_start = (audio_start..head.len().saturating_sub(4))
+        .find(|index| head[*index] == 0xFF && head[*index + 1] & 0xE0 == 0xE0)?;
+    let header = u32_be(head, frame_start)?;
+
+    let version = (header >> 19) & 0x3; // 3 = MPEG 1, 2 = MPEG 2, 0 = MPEG 2.5
+    let layer = (header >> 17) & 0x3; // 3 = layer I, 2 = layer II, 1 = layer III
+    let bitrate_index = ((header >> 12) & 0xF) as usize;
+    let sample_rate_index = ((header >> 10) & 0x3) as usize;
+    let mono = (header >> 6) & 0x3 == 3;
+
+    let sample_rate: u64 = [44_100, 48_000, 32_000].get(sample_rate_index)?
+        / match version {
+            3 => 1,
+            2 => 2,
+            _ => 4,
+        };
+    let samples_per_frame: u64 = match (version, layer) {
+        (_, 3) => 384,
+        (3, _) | (_, 2) => 1_152,
+        _ => 576,
+    };
+
+    // VBR files carry the number of frames in a Xing or Info header
+    let side_info_length = match (version == 3, mono) {
+        (true, false) => 32,
+        (true, true) | (false, false) => 17,
+        (false, true) => 9,
+    };
+    let xing_offset = frame_start + 4 + side_info_length;
+    let xing = head.get(xing_offset..xing_offset + 4);
+    if xing == Some(b"Xing") || xing == Some(b"Info") {
+        let xing_flags = u32_be(head, xing_offset + 4)?;
+        if xing_flags & 0x1 != 0 {
+            let frames = u64::from(u32_be(head, xing_offset + 8)?);
+            return Some((frames * samples_per_frame * 1_000 / sample_rate) as i32);
+        }
+    }
+
+    // otherwise assume a constant bitrate
+    const BITRATES_V1_L3: [u64; 16] = [
+        0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 0,
+    ];
+    const BITRATES_V2_L3: [u64; 16] = [
+        0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160, 0,
+    ];
+    let bitrate = if version == 3 {
+        BITRATES_V1_L3[bitrate_index]
+    } else {
+        BITRATES_V2_L3[bitrate_index]
+    };
+    if bitrate == 0 || layer != 1 {
+        return None;
+    }
+    let audio_size = audio_end.saturating_sub(frame_start as u64);
+    Some((audio_size * 8 / bitrate) as i32)
+}
+
+fn read_id3v1(tail: &[u8]) -> AudioTags {
+    let tag = match tail.len().checked_sub(128).map(|start| &tail[start..]) {
+        Some(tag) if tag.starts_with(b"TAG") => tag,
+        _ => return AudioTags::default(),
+    };
+    let text = |start: usize, length: usize| {
+        let field = &tag[start..start + length];
+        let end = field.iter().position(|byte| *byte == 0).unwrap_or(length);
+        non_empty(
+            &field[..end]
+                .iter()
+                .copied()
+                .map(char::from)
+                .collect::<String>(),
+        )
+    };
+
+    AudioTags {
+        title: text(3, 30),
+        artist: text(33, 30),
+        album: text(63, 30),
+        // ID3v1.1 stores the track number in the last byte of the comment
+        track_number: if tag[125] == 0 && tag[126] != 0 {
+            Some(i32::from(tag[126]))
+        } else {
+            None
+        },
+        ..Default::default()
+    }
+}
+
+// FLAC and Ogg store their tags as Vorbis comments
+
+fn read_vorbis_comments(data: &[u8], tags: &mut AudioTags) {
+    let vendor_length = u32_le(data, 0).unwrap_or_default() as usize;
+    let mut offset = match vendor_length.checked_add(4) {
+        Some(offset) => offset,
+        None => return,
+    };
+    let count = match u32_le(data, offset) {
+        Some(count) => count,
+        None => return,
+    };
+    offset += 4;
+
+    for _ in 0..count {
+        let length = match u32_le(data, offset) {
+            Some(length) => length as usize,
+            None => break,
+        };
+        let comment_end = match (offset + 4).checked_add(length) {
+            Some(comment_end) => comment_end,
+            None => break,
+        };
+        let comment = match data.get(offset + 4..comment_end) {
+            Some(comment) => String::from_utf8_lossy(comment),
+            None => break,
+        };
+        offset = comment_end;
+
+        if let Some((key, value)) = comment.split_once('=') {
+            match key.to_uppercase().as_str() {
+                "TITLE" => tags.title = tags.title.take().or_else(|| non_empty(value)),
+                "ARTIST" => tags.artist = tags.artist.take().or_else(|| non_empty(value)),
+                "ALBUM" => tags.album = tags.album.take().or_else(|| non_empty(value)),
+                "TRACKNUMBER" => tags.track_number = parse_track_number(value),
+                "ISRC" => tags.isrc = non_empty(value),
+                _ => {}
+            }
+        }
+    }
+}
+
+fn read_flac(head: &[u8]) -> AudioTags {
+    let mut tags = AudioTags::default();
+    let mut offset = 4;
+
+    while let Some(block_header) = u32_be(head, offset) {
+        let last_block = block_header & 0x8000_0000 != 0;
+        let block_type = (block_header >> 24) & 0x7F;
+        let block_length = (block_header & 0x00FF_FFFF) as usize;
+        let block_start = offset + 4;
+        let block = &head[block_start..head.len().min(block_start + block_length)];
+
+        match block_type {
+            0 => {
+                if let Some(info) = u64_be(block, 10) {
+                    let sample_rate = info >> 44;
+                    let total_samples = info & 0xF_FFFF_FFFF;
+                    if sample_rate > 0 && total_samples > 0 {
+                        tags.duration = Some((total_samples * 1_000 / sample_rate) as i32);
+                    }
+                }
+            }
+            4 => read_vorbis_comments(block, &mut tags),
+            _ => {}
+        }
+
+        if last_block {
+            break;
+        }
+        offset = block_start + block_length;
+    }
+
+    tags
+}
+
+fn read_ogg(head: &[u8], tail: &[u8]) -> AudioTags {
+    let mut tags = AudioTags::default();
+
+    // reassemble the identification and the comment packet from the pages
+    let mut packets = vec![Vec::new()];
+    let mut offset = 0;
+    while packets.len() <= 2 && head.get(offset..offset + 4) == Some(b"OggS") {
+        let segment_count = match head.get(offset + 26) {
+            Some(segment_count) => usize::from(*segment_count),
+            None => break,
+        };
+        let lacing = match head.get(offset + 27..offset + 27 + segment_count) {
+            Some(lacing) => lacing,
+            None => break,
+        };
+        let mut segment_start = offset + 27 + segment_count;
+        for segment_length in lacing.iter().map(|length| usize::from(*length)) {
+            let segment_end = head.len().min(segment_start + segment_length);
+            if let Some(packet) = packets.last_mut() {
+                packet.extend_from_slice(&head[segment_start.min(segment_end)..segment_end]);
+            }
+            if segment_length < 255 {
+                packets.push(Vec::new());
+            }
+            segment_start += segment_length;
+        }
+        offset = segment_start;
+    }
+
+    let (sample_rate, pre_skip) = match packets.first() {
+        Some(packet) if packet.starts_with(b"\x01vorbis") => {
+            (u32_le(packet, 12).unwrap_or_default(), 0)
+        }
+        Some(packet) if packet.starts_with(b"OpusHead") => {
+            let pre_skip = packet
+                .get(10..12)
+                .map_or(0, |bytes| u16::from_le_bytes([bytes[0], bytes[1]]));
+            (48_000, pre_skip)
+        }
+        _ => return tags,
+    };
+
+    match packets.get(1) {
+        Some(packet) if packet.starts_with(b"\x03vorbis") => {
+            read_vorbis_comments(&packet[7..], &mut tags)
+        }
+        Some(packet) if packet.starts_with(b"OpusTags") => {
+            read_vorbis_comments(&packet[8..], &mut tags)
+        }
+        _ => {}
+    }
+
+    // the granule position of the last page holds the total number of samples
+    let last_page = (0..tail.len().saturating_sub(14))
+        .rev()
+        .find(|index| tail[*index..].starts_with(b"OggS"));
+    if let Some(granule) = last_page
+        .and_then(|index| tail.get(index + 6..index + 14))
+        .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap_or_default()))
+    {
+        let samples = granule.saturating_sub(u64::from(pre_skip));
+        if sample_rate > 0 && samples > 0 {
+            tags.duration = samples
+                .checked_mul(1_000)
+                .map(|samples| (samples / u64::from(sample_rate)) as i32);
+        }
+    }
+
+    tags
+}
+
+// MP4 stores its tags in `moov.udta.meta.ilst`, which might be located at the end of the file
+
+fn read_mp4(head: &[u8], tail: &[u8], file_size: u64) -> AudioTags {
+    let mut tags = AudioTags::default();
+    let tail_start = file_size.saturating_sub(tail.len() as u64);
+    let mut position = 0u64;
+
+    while file_size.saturating_sub(position) >= 8 {
+        let (data, offset) = if position + 8 <= head.len() as u64 {
+            (head, position as usize)
+        } else if position >= tail_start {
+            (tail, (position - tail_start) as usize)
+        } else {
+            break;
+        };
+        let (atom_size, header_size) = match u32_be(data, offset) {
+            Some(1) => match u64_be(data, offset + 8) {
+                Some(size) => (size, 16),
+                None => break,
+            },
+            Some(0) => (file_size - position, 8),
+            Some(size) => (u64::from(size), 8),
+            None => break,
+        };
+        if atom_size < header_size {
+            break;
+        }
+
+        if data.get(offset + 4..offset + 8) == Some(b"moov") {
+            // cover art easily makes `moov` larger than a chunk, its children are read as far
+            // as they are available
+            let end = usize::try_from(atom_size)
+                .map_or(data.len(), |atom_size| offset.saturating_add(atom_size))
+                .min(data.len());
+            if let Some(moov) = data.get(offset + header_size as usize..end) {
+                read_moov(moov, &mut tags);
+            }
+            break;
+        }
+        position = match position.checked_add(atom_size) {
+            Some(position) => position,
+            None => break,
+        };
+    }
+
+    tags
+}
+
+// the content of the last atom is cut off if `data` ends within it, `complete` tells whether
+// the content is whole
+fn mp4_atoms(data: &[u8]) -> impl Iterator<Item = (&[u8], &[u8], bool)> {
+    let mut offset = 0;
+    std::iter::from_fn(move || {
+        let size = (u32_be(data, offset)? as usize).max(8);
+        let name = data.get(offset + 4..offset + 8)?;
+        let end = offset.checked_add(size)?;
+        let content = data.get(offset + 8..end.min(data.len()))?;
+        offset = end;
+        Some((name, content, end <= data.len()))
+    })
+}
+
+fn read_moov(moov: &[u8], tags: &mut AudioTags) {
+    for (name, content, _) in mp4_atoms(moov) {
+        match name {
+            b"mvhd" => {
+                let (timescale, duration) = if content.first() == Some(&1) {
+                    (u32_be(content, 20), u64_be(content, 24))
+                } else {
+                    (u32_be(content, 12), u32_be(content, 16).map(u64::from))
+                };
+                if let (Some(timescale), Some(duration)) = (timescale, duration) {
+                    if timescale > 0 {
+                        tags.duration = duration
+                            .checked_mul(1_000)
+                            .map(|duration| (duration / u64::from(timescale)) as i32);
+                    }
+                }
+            }
+            b"udta" => {
+                for (name, content, _) in mp4_atoms(content) {
+                    // `meta` is a full atom with 4 bytes of version and flags
+                    if name == b"meta" && content.len() > 4 {
+                        for (name, content, _) in mp4_atoms(&content[4..]) {
+                            if name == b"ilst" {
+                                read_ilst(content, tags);
+                            }
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn read_ilst(ilst: &[u8], tags: &mut AudioTags) {
+    // a value that is cut off is left out rather than read partially
+    for (name, content, _) in mp4_atoms(ilst) {
+        let mut freeform_name = None;
+        let mut value = None;
+        for (child_name, child_content, _) in
+            mp4_atoms(content).filter(|(_, _, complete)| *complete)
+        {
+            match child_name {
+                // skip version, flags and locale
+                b"data" if child_content.len() >= 8 => value = Some(&child_content[8..]),
+                b"name" if child_content.len() >= 4 => {
+                    freeform_name = Some(String::from_utf8_lossy(&child_content[4..]).into_owned())
+                }
+                _ => {}
+            }
+        }
+        let value = match value {
+            Some(value) => value,
+            None => continue,
+        };
+        let text = || non_empty(&String::from_utf8_lossy(value));
+
+        match name {
+            b"\xA9nam" => tags.title = text(),
+            b"\xA9ART" => tags.artist = text(),
+            b"\xA9alb" => tags.album = text(),
+            b"trkn" => {
+                tags.track_number = u16_be(value, 2).map(i32::from).filter(|number| *number > 0)
+            }
+            b"----" if freeform_name.as_deref() == Some("ISRC") => tags.isrc = text(),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    fn id3v2_frame(id: &[u8], text: &str) -> Vec<u8> {
+        let mut frame = id.to_vec();
+        frame.extend_from_slice(&(text.len() as u32 + 1).to_be_bytes());
+        frame.extend_from_slice(&[0, 0, 3]);
+        frame.extend_from_slice(text.as_bytes());
+        frame
+    }
+
+    #[wasm_bindgen_test]
+    fn id3v2() {
+        let frames = [
+            id3v2_frame(b"TIT2", "Title"),
+            id3v2_frame(b"TPE1", "Artist"),
+            id3v2_frame(b"TRCK", "3/12"),
+            id3v2_frame(b"TLEN", "185000"),
+            id3v2_frame(b"TSRC", "USRC17607839"),
+        ]
+        .concat();
+        let mut file = b"ID3\x03\x00\x00".to_vec();
+        file.extend_from_slice(&[0, 0, 0, frames.len() as u8]);
+        file.extend_from_slice(&frames);
+        // ID3v1 fills in the missing album
+        let mut id3v1 = b"TAG".to_vec();
+        id3v1.resize(63, 0);
+        id3v1.extend_from_slice(b"Album");
+        id3v1.resize(128, 0);
+        file.extend_from_slice(&id3v1);
+
+        assert_eq!(
+            AudioTags {
+                title: Some("Title".to_string()),
+                artist: Some("Artist".to_string()),
+                album: Some("Album".to_string()),
+                track_number: Some(3),
+                duration: Some(185_000),
+                isrc: Some("USRC17607839".to_string()),
+            },
+            read_audio_tags(&file, &file, file.len() as u64)
+        );
+    }
+
+    fn vorbis_comments(comments: &[&str]) -> Vec<u8> {
+        let mut data = 6u32.to_le_bytes().to_vec();
+        data.extend_from_slice(b"vendor");
+        data.extend_from_slice(&(comments.len() as u32).to_le_bytes());
+        for comment in comments {
+            data.extend_from_slice(&(comment.len() as u32).to_le_bytes());
+            data.extend_from_slice(comment.as_bytes());
+        }
+        data
+    }
+
+    fn ogg_page(granule: u64, packets: &[&[u8]]) -> Vec<u8> {
+        let mut lacing = Vec::new();
+        for packet in packets {
+            lacing.resize(lacing.len() + packet.len() / 255, 255);
+            lacing.push((packet.len() % 255) as u8);
+        }
+        let mut page = b"OggS\x00\x00".to_vec();
+        page.extend_from_slice(&granule.to_le_bytes());
+        page.resize(26, 0);
+        page.push(lacing.len() as u8);
+        page.extend_from_slice(&lacing);
+        for packet in packets {
+            page.extend_from_slice(packet);
+        }
+        page
+    }
+
+    fn mp4_atom(name: &[u8], content: &[u8]) -> Vec<u8> {
+        let mut atom = (content.len() as u32 + 8).to_be_bytes().to_vec();
+        atom.extend_from_slice(name);
+        atom.extend_from_slice(content);
+        atom
+    }
+
+    fn mp4_data(value: &[u8]) -> Vec<u8> {
+        mp4_atom(b"data", &[&[0, 0, 0, 1, 0, 0, 0, 0][..], value].concat())
+    }
+
+    #[wasm_bindgen_test]
+    fn flac() {
+        let mut comments = 0u32.to_le_bytes().to_vec();
+        comments.extend_from_slice(&2u32.to_le_bytes());
+        for comment in ["TITLE=Title", "artist=Artist"].iter() {
+            comments.extend_from_slice(&(comment.len() as u32).to_le_bytes());
+            comments.extend_from_slice(comment.as_bytes());
+        }
+        // 44.1 kHz, 2 channels, 16 bits, 441000 samples
+        let mut stream_info = vec![0; 10];
+        stream_info.extend_from_slice(
+            &((44_100u64 << 44) | (1 << 41) | (15 << 36) | 441_000).to_be_bytes(),
+        );
+        stream_info.resize(34, 0);
+
+        let mut file = b"fLaC".to_vec();
+        file.extend_from_slice(&(stream_info.len() as u32).to_be_bytes());
+        file.extend_from_slice(&stream_info);
+        file.extend_from_slice(&(0x8400_0000 | comments.len() as u32).to_be_bytes());
+        file.extend_from_slice(&comments);
+
+        let tags = read_audio_tags(&file, &file, file.len() as u64);
+        assert_eq!(Some("Title".to_string()), tags.title);
+        assert_eq!(Some("Artist".to_string()), tags.artist);
+        assert_eq!(Some(10_000), tags.duration);
+    }
+
+    #[wasm_bindgen_test]
+    fn opus() {
+        let mut head = b"OpusHead\x01\x02".to_vec();
+        head.extend_from_slice(&312u16.to_le_bytes());
+        head.extend_from_slice(&48_000u32.to_le_bytes());
+        head.extend_from_slice(&[0, 0, 0]);
+        // the comment packet is longer than a segment, so it is spread over several ones
+        let long_album = format!("ALBUM={}", "a".repeat(300));
+        let mut comments = b"OpusTags".to_vec();
+        comments.extend_from_slice(&vorbis_comments(&[
+            "TITLE=Title",
+            "ARTIST=Artist",
+            &long_album,
+            "TRACKNUMBER=3/12",
+            "ISRC=USRC17607839",
+        ]));
+
+        let mut file = ogg_page(0, &[&head]);
+        file.extend_from_slice(&ogg_page(0, &[&comments]));
+        file.extend_from_slice(&ogg_page(48_000 * 5 + 312, &[&[0; 10]]));
+
+        assert_eq!(
+            AudioTags {
+                title: Some("Title".to_string()),
+                artist: Some("Artist".to_string()),
+                album: Some("a".repeat(300)),
+                track_number: Some(3),
+                duration: Some(5_000),
+                isrc: Some("USRC17607839".to_string()),
+            },
+            read_audio_tags(&file, &file, file.len() as u64)
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn vorbis() {
+        let mut identification = b"\x01vorbis".to_vec();
+        identification.extend_from_slice(&[0, 0, 0, 0, 2]);
+        identification.extend_from_slice(&44_100u32.to_le_bytes());
+        identification.resize(30, 0);
+        let mut comments = b"\x03vorbis".to_vec();
+        comments.extend_from_slice(&vorbis_comments(&["title=Title"]));
+
+        let mut file = ogg_page(0, &[&identification]);
+        file.extend_from_slice(&ogg_page(0, &[&comments]));
+        file.extend_from_slice(&ogg_page(441_000, &[&[0; 10]]));
+
+        let tags = read_audio_tags(&file, &file, file.len() as u64);
+        assert_eq!(Some("Title".to_string()), tags.title);
+        assert_eq!(Some(10_000), tags.duration);
+    }
+
+    #[wasm_bindgen_test]
+    fn mp4() {
+        let mut mvhd = vec![0; 12];
+        mvhd.extend_from_slice(&1_000u32.to_be_bytes());
+        mvhd.extend_from_slice(&215_000u32.to_be_bytes());
+        mvhd.resize(100, 0);
+        let mut isrc = mp4_atom(b"mean", b"\x00\x00\x00\x00com.apple.iTunes");
+        isrc.extend_from_slice(&mp4_atom(b"name", b"\x00\x00\x00\x00ISRC"));
+        isrc.extend_from_slice(&mp4_data(b"USRC17607839"));
+        let ilst = [
+            mp4_atom(b"\xA9nam", &mp4_data(b"Title")),
+            mp4_atom(b"\xA9ART", &mp4_data(b"Artist")),
+            mp4_atom(b"\xA9alb", &mp4_data(b"Album")),
+            mp4_atom(b"trkn", &mp4_data(&[0, 0, 0, 3, 0, 12, 0, 0])),
+            mp4_atom(b"----", &isrc),
+            // cover art that doesn't fit into the chunk
+            mp4_atom(b"covr", &mp4_data(&vec![0xFF; 4_000])),
+        ]
+        .concat();
+        let meta = [&[0, 0, 0, 0][..], &mp4_atom(b"ilst", &ilst)].concat();
+        let moov = [
+            mp4_atom(b"mvhd", &mvhd),
+            mp4_atom(b"udta", &mp4_atom(b"meta", &meta)),
+        ]
+        .concat();
+
+        let mut file = mp4_atom(b"ftyp", b"M4A \x00\x00\x00\x00");
+        file.extend_from_slice(&mp4_atom(b"moov", &moov));
+        file.extend_from_slice(&mp4_atom(b"mdat", &[0; 100]));
+        let head = &file[..file.len() - 3_000];
+        let tail = &file[file.len() - 100..];
+
+        assert_eq!(
+            AudioTags {
+                title: Some("Title".to_string()),
+                artist: Some("Artist".to_string()),
+                album: Some("Album".to_string()),
+                track_number: Some(3),
+                duration: Some(215_000),
+                isrc: Some("USRC17607839".to_string()),
+            },
+            read_audio_tags(head, tail, file.len() as u64)
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn corrupt_sizes() {
+        // an ID3v2 frame that claims to be larger than the address space
+        let mut frame = id3v2_frame(b"TIT2", "Title");
+        frame[4..8].copy_from_slice(&u32::MAX.to_be_bytes());
+        let mut file = b"ID3\x03\x00\x00".to_vec();
+        file.extend_from_slice(&[0, 0, 0, frame.len() as u8]);
+        file.extend_from_slice(&frame);
+        assert_eq!(None, read_audio_tags(&file, &file, file.len() as u64).title);
+
+        let mut comments = vorbis_comments(&["TITLE=Title"]);
+        comments[0..4].copy_from_slice(&u32::MAX.to_le_bytes());
+        let mut tags = AudioTags::default();
+        read_vorbis_comments(&comments, &mut tags);
+        let mut comments = vorbis_comments(&["TITLE=Title"]);
+        comments[14..18].copy_from_slice(&u32::MAX.to_le_bytes());
+        read_vorbis_comments(&comments, &mut tags);
+        assert_eq!(None, tags.title);
+
+        // a 64 bit atom size that overflows the position and a duration that overflows
+        // the milliseconds
+        let mut mvhd = vec![1, 0, 0, 0];
+        mvhd.resize(20, 0);
+        mvhd.extend_from_slice(&1u32.to_be_bytes());
+        mvhd.extend_from_slice(&u64::MAX.to_be_bytes());
+        mvhd.resize(112, 0);
+        let mut file = mp4_atom(b"ftyp", b"M4A \x00\x00\x00\x00");
+        file.extend_from_slice(&1u32.to_be_bytes());
+        file.extend_from_slice(b"free");
+        file.extend_from_slice(&u64::MAX.to_be_bytes());
+        assert_eq!(
+            AudioTags::default(),
+            read_audio_tags(&file, &file, u64::MAX)
+        );
+        let mut file = mp4_atom(b"ftyp", b"M4A \x00\x00\x00\x00");
+        file.extend_from_slice(&mp4_atom(b"moov", &mp4_atom(b"mvhd", &mvhd)));
+        assert_eq!(
+            None,
+            read_audio_tags(&file, &file, file.len() as u64).duration
+        );
+    }
+}
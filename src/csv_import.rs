@@ -0,0 +1,367 @@
+use crate::m3u::non_empty;
+use crate::playlist_types::*;
+use crate::wpl::parse_clock;
+
+use strum_macros::{Display, EnumIter, EnumString};
+
+const DELIMITERS: [u8; 4] = [b',', b';', b'\t', b'|'];
+
+#[derive(Debug, Clone, Copy, PartialEq, Display, EnumIter, EnumString)]
+pub enum DurationUnit {
+    #[strum(serialize = "Milliseconds")]
+    Milliseconds,
+    #[strum(serialize = "Seconds")]
+    Seconds,
+    #[strum(serialize = "Minutes:Seconds")]
+    Clock,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnMapping {
+    pub columns: Vec<Option<TrackField>>,
+    pub duration_unit: DurationUnit,
+}
+
+pub fn detect_delimiter(content: &str) -> u8 {
+    let lines = content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .take(10)
+        .collect::<Vec<_>>();
+
+    // prefer the delimiter that occurs most often while splitting all sample lines equally
+    DELIMITERS
+        .iter()
+        .copied()
+        .map(|delimiter| {
+            let counts = lines
+                .iter()
+                .map(|line| count_unquoted(line, delimiter))
+                .collect::<Vec<_>>();
+            let consistent = counts.windows(2).all(|pair| pair[0] == pair[1]);
+            let min_count = counts.iter().copied().min().unwrap_or_default();
+            (delimiter, consistent, min_count)
+        })
+        .filter(|(_, _, min_count)| *min_count > 0)
+        .max_by_key(|(_, consistent, min_count)| (*consistent, *min_count))
+        .map_or(b',', |(delimiter, _, _)| delimiter)
+}
+
+fn count_unquoted(line: &str, delimiter: u8) -> usize {
+    let mut in_quotes = false;
+    line.bytes()
+        .filter(|byte| {
+            if *byte == b'"' {
+                in_quotes = !in_quotes;
+            }
+            *byte == delimiter && !in_quotes
+        })
+        .count()
+}
+
+pub fn parse_rows(content: &str, delimiter: u8) -> Vec<Vec<String>> {
+    csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(content.as_bytes())
+        .records()
+        .filter_map(Result::ok)
+        .map(|record| record.iter().map(|cell| cell.trim().to_owned()).collect())
+        .filter(|row: &Vec<String>| row.iter().any(|cell| !cell.is_empty()))
+        .collect()
+}
+
+// guesses the mapping from column names, returns `None` if the row doesn't look like a header
+pub fn guess_mapping(header: &[String]) -> Option<ColumnMapping> {
+    let mut fields = Vec::with_capacity(header.len());
+    let mut duration_unit = DurationUnit::Milliseconds;
+
+    for name in header {
+        let name = name.to_lowercase();
+        let field = if name.contains("disc") {
+            None
+        } else if name.contains("uri") || name.contains("url") || name == "identifier" {
+            if name.starts_with("track") || name == "uri" || name == "identifier" {
+                Some(TrackField::Identifier)
+            } else {
+                None
+            }
+        } else if name == "isrc" {
+            Some(TrackField::Isrc)
+        } else if name == "location" || name == "path" {
+            Some(TrackField::Location)
+        } else if name.contains("album") && name.contains("artist") {
+            None
+        } else if name.contains("artist") || name == "creator" {
+            Some(TrackField::Artist)
+        } else if name.contains("album") {
+            Some(TrackField::Album)
+        } else if name.contains("duration") || name.contains("length") || name == "time" {
+            if name.contains("ms") || name.contains("milli") {
+                duration_unit = DurationUnit::Milliseconds;
+            } else if name.contains("sec") || name.contains("(s)") {
+                duration_unit = DurationUnit::Seconds;
+            } else {
+                duration_unit = DurationUnit::Clock;
+            }
+            Some(TrackField::Duration)
+        } else if name.contains("number") || name == "#" || name == "tracknum" || name == "no" {
+            Some(TrackField::TrackNumber)
+        } else if name.contains("title")
+            || name.contains("name")
+            || name == "song"
+            || name == "track"
+        {
+            Some(TrackField::Title)
+        } else {
+            None
+        };
+        let explicit =
+            ["track number", "tracknumber", "track no", "track #"].contains(&name.as_str());
+        fields.push((field, explicit));
+    }
+
+    // every field can only be mapped to a single column, an explicit name wins over the first match
+    let chosen_column = |field: TrackField| {
+        fields
+            .iter()
+            .position(|candidate| *candidate == (Some(field), true))
+            .or_else(|| {
+                fields
+                    .iter()
+                    .position(|(candidate, _)| *candidate == Some(field))
+            })
+    };
+    let columns = fields
+        .iter()
+        .enumerate()
+        .map(|(column, (field, _))| field.filter(|field| chosen_column(*field) == Some(column)))
+        .collect::<Vec<_>>();
+
+    if columns.iter().any(Option::is_some) {
+        Some(ColumnMapping {
+            columns,
+            duration_unit,
+        })
+    } else {
+        None
+    }
+}
+
+pub fn rows_to_playlist(rows: &[Vec<String>], mapping: &ColumnMapping, title: String) -> Playlist {
+    let tracks = rows
+        .iter()
+        .map(|row| {
+            let mut track = Track::default();
+            for (cell, field) in row.iter().zip(&mapping.columns) {
+                let value = non_empty(cell);
+                match field {
+                    Some(TrackField::Title) => track.title = value,
+                    Some(TrackField::Artist) => track.artist = value,
+                    Some(TrackField::Album) => track.album = value,
+                    Some(TrackField::Duration) => {
+                        track.duration =
+                            value.and_then(|value| parse_duration(&value, mapping.duration_unit))
+                    }
+                    Some(TrackField::TrackNumber) => {
+                        track.track_number = value.and_then(|value| value.parse().ok())
+                    }
+                    Some(TrackField::Identifier) => track.identifier = value,
+                    Some(TrackField::Isrc) => track.isrc = value.map(|isrc| isrc.to_uppercase()),
+                    Some(TrackField::Location) => track.location = value,
+                    None => {}
+                }
+            }
+            track
+        })
+        .filter(|track| track.title.is_some() || track.identifier.is_some() || track.isrc.is_some())
+        .collect();
+    Playlist::with_tracks_and_title(tracks, title)
+}
+
+// the header is chosen so that `guess_mapping` recognizes the columns when the file is imported again
+pub fn write_csv(playlist: &Playlist) -> String {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer
+        .write_record([
+            "Title",
+            "Artist",
+            "Album",
+            "Duration (ms)",
+            "Track number",
+            "Identifier",
+            "Location",
+            "ISRC",
+        ])
+        .expect("write header");
+    for track in &playlist.track_list.tracks {
+        let cell = |value: &Option<String>| value.clone().unwrap_or_default();
+        let number = |value: Option<i32>| value.map(|value| value.to_string()).unwrap_or_default();
+        writer
+            .write_record([
+                cell(&track.title),
+                cell(&track.artist),
+                cell(&track.album),
+                number(track.duration),
+                number(track.track_number),
+                cell(&track.identifier),
+                cell(&track.location),
+                cell(&track.isrc),
+            ])
+            .expect("write track");
+    }
+    let content = writer.into_inner().expect("flush tracks");
+    String::from_utf8(content).expect("valid UTF-8")
+}
+
+fn parse_duration(value: &str, unit: DurationUnit) -> Option<i32> {
+    match unit {
+        DurationUnit::Milliseconds => value.parse::<f64>().ok().map(|ms| ms.round() as i32),
+        DurationUnit::Seconds => value
+            .parse::<f64>()
+            .ok()
+            .map(|seconds| (seconds * 1_000.0).round() as i32),
+        DurationUnit::Clock => parse_clock(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    fn header(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[wasm_bindgen_test]
+    fn delimiter() {
+        assert_eq!(b',', detect_delimiter("Title,Artist\nA,B\n"));
+        assert_eq!(b';', detect_delimiter("Title;Artist\n\"A, B\";C\n"));
+        assert_eq!(
+            b'\t',
+            detect_delimiter("Title\tArtist\tAlbum\nA\tB, C\tD\n")
+        );
+        assert_eq!(b',', detect_delimiter("Title\nA\n"));
+    }
+
+    #[wasm_bindgen_test]
+    fn exportify_mapping() {
+        let mapping = guess_mapping(&header(&[
+            "Track URI",
+            "Track Name",
+            "Artist URI(s)",
+            "Artist Name(s)",
+            "Album URI",
+            "Album Name",
+            "Album Artist URI(s)",
+            "Album Artist Name(s)",
+            "Album Release Date",
+            "Album Image URL",
+            "Disc Number",
+            "Track Number",
+            "Track Duration (ms)",
+            "Track Preview URL",
+            "Explicit",
+            "Popularity",
+            "ISRC",
+            "Added By",
+            "Added At",
+        ]))
+        .unwrap();
+
+        let mapped = |field| {
+            mapping
+                .columns
+                .iter()
+                .position(|column| *column == Some(field))
+        };
+        assert_eq!(Some(0), mapped(TrackField::Identifier));
+        assert_eq!(Some(1), mapped(TrackField::Title));
+        assert_eq!(Some(3), mapped(TrackField::Artist));
+        assert_eq!(Some(5), mapped(TrackField::Album));
+        assert_eq!(None, mapping.columns[10]);
+        assert_eq!(Some(11), mapped(TrackField::TrackNumber));
+        assert_eq!(Some(12), mapped(TrackField::Duration));
+        assert_eq!(Some(16), mapped(TrackField::Isrc));
+        assert_eq!(DurationUnit::Milliseconds, mapping.duration_unit);
+    }
+
+    #[wasm_bindgen_test]
+    fn explicit_track_number() {
+        let mapping = guess_mapping(&header(&["No", "Title", "Track Number"])).unwrap();
+        assert_eq!(
+            vec![None, Some(TrackField::Title), Some(TrackField::TrackNumber)],
+            mapping.columns
+        );
+        assert_eq!(None, guess_mapping(&header(&["Halo", "Beyonce", "4:21"])));
+    }
+
+    #[wasm_bindgen_test]
+    fn rows() {
+        let rows = parse_rows(
+            "Artist\tTitle\tLength\n\nA\tOne\t3:35\n\tTwo\t\n\t\t1:00\n",
+            b'\t',
+        );
+        assert_eq!(4, rows.len());
+        let mapping = guess_mapping(&rows[0]).unwrap();
+        assert_eq!(DurationUnit::Clock, mapping.duration_unit);
+
+        let playlist = rows_to_playlist(&rows[1..], &mapping, "Playlist".to_string());
+        assert_eq!(Some("Playlist".to_string()), playlist.title);
+        assert_eq!(
+            vec![
+                Track {
+                    artist: Some("A".to_string()),
+                    title: Some("One".to_string()),
+                    duration: Some(215_000),
+                    ..Default::default()
+                },
+                Track {
+                    title: Some("Two".to_string()),
+                    ..Default::default()
+                },
+            ],
+            playlist.track_list.tracks
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn isrc_only_rows() {
+        let rows = parse_rows("ISRC\nusrc17607839\nGBAYE0601498\n", b',');
+        let mapping = guess_mapping(&rows[0]).unwrap();
+        let playlist = rows_to_playlist(&rows[1..], &mapping, "Playlist".to_string());
+        let tracks = &playlist.track_list.tracks;
+        assert_eq!(2, tracks.len());
+        assert_eq!(Some("USRC17607839".to_string()), tracks[0].isrc);
+        assert_eq!(None, tracks[0].identifier);
+        assert_ne!(tracks[0].id(), tracks[1].id());
+    }
+
+    #[wasm_bindgen_test]
+    fn write_round_trip() {
+        let playlist = Playlist::with_tracks_and_title(
+            vec![Track {
+                identifier: Some("spotify:track:1".to_string()),
+                title: Some("Title, \"quoted\"".to_string()),
+                artist: Some("Artist".to_string()),
+                album: Some("Album".to_string()),
+                track_number: Some(3),
+                duration: Some(215_000),
+                location: Some("music/01.mp3".to_string()),
+                isrc: Some("USRC17607839".to_string()),
+                ..Default::default()
+            }],
+            "Playlist".to_string(),
+        );
+
+        let content = write_csv(&playlist);
+        let rows = parse_rows(&content, detect_delimiter(&content));
+        let mapping = guess_mapping(&rows[0]).unwrap();
+        assert_eq!(
+            playlist,
+            rows_to_playlist(&rows[1..], &mapping, "Playlist".to_string())
+        );
+    }
+}
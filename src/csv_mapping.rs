@@ -0,0 +1,248 @@
+use crate::csv_import::*;
+use crate::playlist_types::*;
+use strum::IntoEnumIterator;
+use yew::{html::Html, prelude::*, Properties};
+
+use std::rc::Rc;
+use std::str::FromStr;
+
+const PREVIEW_ROWS: usize = 5;
+const DELIMITER_NAMES: [(u8, &str); 4] = [
+    (b',', "Comma"),
+    (b';', "Semicolon"),
+    (b'\t', "Tab"),
+    (b'|', "Pipe"),
+];
+
+pub struct CsvMapping {
+    link: ComponentLink<Self>,
+    props: Props,
+    state: State,
+}
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct Props {
+    pub content: Rc<String>,
+    pub title: String,
+    pub onconfirm: Callback<Playlist>,
+}
+
+pub struct State {
+    delimiter: u8,
+    has_header: bool,
+    rows: Vec<Vec<String>>,
+    mapping: ColumnMapping,
+}
+
+pub enum Msg {
+    DelimiterSelected(u8),
+    HasHeaderToggled,
+    ColumnFieldSelected(usize, Option<TrackField>),
+    DurationUnitSelected(DurationUnit),
+    Confirm,
+    Noop,
+}
+
+impl Component for CsvMapping {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let state = State::parse(&props.content, detect_delimiter(&props.content));
+        CsvMapping { link, props, state }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::DelimiterSelected(delimiter) => {
+                self.state = State::parse(&self.props.content, delimiter);
+            }
+            Msg::HasHeaderToggled => {
+                self.state.has_header = !self.state.has_header;
+            }
+            Msg::ColumnFieldSelected(column, field) => {
+                // a field can only be mapped to a single column
+                for mapped_field in self.state.mapping.columns.iter_mut() {
+                    if field.is_some() && *mapped_field == field {
+                        *mapped_field = None;
+                    }
+                }
+                self.state.mapping.columns[column] = field;
+            }
+            Msg::DurationUnitSelected(duration_unit) => {
+                self.state.mapping.duration_unit = duration_unit;
+            }
+            Msg::Confirm => {
+                let playlist = rows_to_playlist(
+                    self.state.data_rows(),
+                    &self.state.mapping,
+                    self.props.title.clone(),
+                );
+                self.props.onconfirm.emit(playlist);
+            }
+            Msg::Noop => {}
+        }
+        true
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        if props.content != self.props.content {
+            self.state = State::parse(&props.content, detect_delimiter(&props.content));
+        }
+        self.props = props;
+        true
+    }
+
+    fn view(&self) -> Html {
+        let onchange_delimiter = self
+            .link
+            .callback(|event: yew::html::ChangeData| match event {
+                yew::html::ChangeData::Select(select) => select
+                    .value()
+                    .parse()
+                    .map_or(Msg::Noop, Msg::DelimiterSelected),
+                _ => Msg::Noop,
+            });
+        let onclick_has_header = self.link.callback(|_| Msg::HasHeaderToggled);
+        let onchange_duration_unit =
+            self.link
+                .callback(|event: yew::html::ChangeData| match event {
+                    yew::html::ChangeData::Select(select) => {
+                        DurationUnit::from_str(&select.value())
+                            .map_or(Msg::Noop, Msg::DurationUnitSelected)
+                    }
+                    _ => Msg::Noop,
+                });
+        let onclick_confirm = self.link.callback(|_| Msg::Confirm);
+
+        let header = if self.state.has_header {
+            self.state.rows.first()
+        } else {
+            None
+        };
+        let can_confirm = self.state.mapping.columns.iter().any(|field| {
+            *field == Some(TrackField::Title)
+                || *field == Some(TrackField::Identifier)
+                || *field == Some(TrackField::Isrc)
+        });
+
+        let render_column_select = |column: usize| {
+            let onchange = self
+                .link
+                .callback(move |event: yew::html::ChangeData| match event {
+                    yew::html::ChangeData::Select(select) => {
+                        Msg::ColumnFieldSelected(column, TrackField::from_str(&select.value()).ok())
+                    }
+                    _ => Msg::Noop,
+                });
+            let mapped_field = self.state.mapping.columns[column];
+            html! {
+                <select onchange=onchange>
+                    <option value={""} selected={mapped_field.is_none()}>{"Ignore"}</option>
+                    {
+                        for TrackField::iter().map(|field| html! {
+                            <option value={field.to_string()} selected={mapped_field == Some(field)}>
+                                {field.to_string()}
+                            </option>
+                        })
+                    }
+                </select>
+            }
+        };
+
+        html! {
+            <div class="csv-mapping">
+                <div>
+                    <span class="form">{"Delimiter:"}</span>
+                    <select class="inline" onchange=onchange_delimiter>
+                        {
+                            for DELIMITER_NAMES.iter().map(|(delimiter, name)| html! {
+                                <option value={delimiter.to_string()} selected={*delimiter == self.state.delimiter}>
+                                    {name}
+                                </option>
+                            })
+                        }
+                    </select>
+                    <label class="inline">
+                        <input type="checkbox" checked=self.state.has_header onclick=onclick_has_header/>
+                        {"First row contains column names"}
+                    </label>
+                    <span class="form inline">{"Duration unit:"}</span>
+                    <select class="inline" onchange=onchange_duration_unit>
+                        {
+                            for DurationUnit::iter().map(|duration_unit| html! {
+                                <option
+                                    value={duration_unit.to_string()}
+                                    selected={duration_unit == self.state.mapping.duration_unit}
+                                >
+                                    {duration_unit.to_string()}
+                                </option>
+                            })
+                        }
+                    </select>
+                </div>
+                <table>
+                    <thead>
+                        <tr>
+                            {
+                                for (0..self.state.mapping.columns.len()).map(|column| html! {
+                                    <th>
+                                        {render_column_select(column)}
+                                        <div>
+                                            {header.and_then(|header| header.get(column)).map(String::as_str).unwrap_or_default()}
+                                        </div>
+                                    </th>
+                                })
+                            }
+                        </tr>
+                    </thead>
+                    <tbody>
+                        {
+                            for self.state.data_rows().iter().take(PREVIEW_ROWS).map(|row| html! {
+                                <tr>
+                                    {for row.iter().map(|cell| html! {<td>{cell}</td>})}
+                                </tr>
+                            })
+                        }
+                    </tbody>
+                </table>
+                <div>
+                    {format!("{} rows", self.state.data_rows().len())}
+                </div>
+                <br/>
+                <button class="main" onclick=onclick_confirm disabled=!can_confirm>
+                    {"Use column mapping"}
+                </button>
+            </div>
+        }
+    }
+}
+
+impl State {
+    fn parse(content: &str, delimiter: u8) -> State {
+        let rows = parse_rows(content, delimiter);
+        let column_count = rows.iter().map(Vec::len).max().unwrap_or_default();
+        let guessed_mapping = rows.first().and_then(|header| guess_mapping(header));
+        let has_header = guessed_mapping.is_some();
+        let mut mapping = guessed_mapping.unwrap_or(ColumnMapping {
+            columns: Vec::new(),
+            duration_unit: DurationUnit::Milliseconds,
+        });
+        mapping.columns.resize(column_count, None);
+
+        State {
+            delimiter,
+            has_header,
+            rows,
+            mapping,
+        }
+    }
+
+    fn data_rows(&self) -> &[Vec<String>] {
+        if self.has_header && !self.rows.is_empty() {
+            &self.rows[1..]
+        } else {
+            &self.rows
+        }
+    }
+}
@@ -1,14 +1,26 @@
 use crate::app::SpotifyUser;
+use crate::audio_tags::{is_audio_file, read_audio_tags, AudioTags, HEAD_SIZE, TAIL_SIZE};
 use crate::download_file;
+use crate::import_summary::{ImportSummary, SummaryFormat};
+use crate::itunes::Library;
+use crate::match_report::{write_report, MatchReportRow, ReportFormat, Selection};
+use crate::matcher::MatchSettings;
+use crate::merge::{merge_playlists, MergeMode};
+use crate::parse_error::ParseError;
+use crate::path_metadata::{infer_from_path, DEFAULT_PATH_PATTERNS};
+use crate::playlist_format::{detect_format, parse_input, Detection, ParsedInput, PlaylistFormat};
 use crate::playlist_types::*;
+use crate::search_query::search_queries;
 use crate::spotify_types::{
-    SpotifyCreatePlaylist, SpotifyPagination, SpotifyPlaylist, SpotifyResult, SpotifyTracks,
+    SpotifyCreatePlaylist, SpotifyPagination, SpotifyPlaylist, SpotifyPlaylistItem, SpotifyResult,
+    SpotifyTrack, SpotifyTracks,
 };
-use crate::TrackList;
+use crate::{CsvMapping, MatchSettingsPanel, TrackList};
 
 use anyhow::Error;
 use http::{Request, Response};
 use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use strum::IntoEnumIterator;
 use yew::services::fetch::{FetchService, FetchTask};
 use yew::services::reader::{File, FileData, ReaderService, ReaderTask};
 use yew::services::storage::{Area, StorageService};
@@ -20,10 +32,16 @@ use yew::{
     Properties,
 };
 
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::ops::Range;
+use std::str::FromStr;
 use std::{rc::Rc, time::Duration};
 
 const LS_ID_MAPPING: &str = "id-mapping";
+const LS_PATH_PATTERNS: &str = "path-patterns";
+const LS_MANUAL_MAPPINGS: &str = "manual-mappings";
+const LS_MATCH_SETTINGS: &str = "match-settings";
+const DEFAULT_EXPORT_FILE_NAME: &str = "spotify-playlist-importer";
 
 pub struct Import {
     link: ComponentLink<Self>,
@@ -33,6 +51,7 @@ pub struct Import {
     state: State,
     fetch_tasks: Vec<FetchTask>,
     reader_tasks: Vec<ReaderTask>,
+    audio_reader_tasks: Vec<ReaderTask>,
     _interval_task: IntervalTask,
 }
 
@@ -41,6 +60,52 @@ pub struct Props {
     pub spotify_user: Rc<SpotifyUser>,
 }
 
+pub enum InFileContent {
+    Playlists(Vec<Playlist>),
+    Library(Rc<Library>),
+    Table(Rc<String>),
+    Error(ParseError),
+}
+
+pub struct InFile {
+    data: FileData,
+    detection: Detection,
+    content: InFileContent,
+    skipped_tracks: Vec<ParseError>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum JobStatus {
+    Ready,
+    Importing,
+    Done,
+    Failed,
+}
+
+// in batch mode every input playlist is imported into a Spotify playlist of its own
+pub struct Job {
+    title: String,
+    tracks: Range<usize>,
+    target: Option<String>,
+    status: JobStatus,
+}
+
+#[derive(Clone, Copy)]
+pub enum AudioChunk {
+    Head,
+    Tail,
+}
+
+pub struct AudioFile {
+    name: String,
+    size: u64,
+    head: Option<Vec<u8>>,
+    tail: Option<Vec<u8>>,
+    // the chunks are dropped once the tags are read, a whole music folder wouldn't fit into memory
+    tags: Option<AudioTags>,
+    failed: bool,
+}
+
 #[derive(Clone, Copy)]
 pub enum FetchInitiator {
     Auto(usize),
@@ -49,15 +114,41 @@ pub enum FetchInitiator {
 
 pub struct State {
     in_tracks: Rc<Vec<Rc<Track>>>,
-    out_tracks: Rc<HashMap<String, Rc<Vec<(f64, Track)>>>>,
+    in_title: Option<String>,
+    in_files: Vec<InFile>,
+    loading_in_files: Vec<Option<FileData>>,
+    selected_library_playlists: Vec<usize>,
+    merge_mode: MergeMode,
+    batch_mode: bool,
+    jobs: Vec<Job>,
+    active_job: usize,
+    importing_job: Option<usize>,
+    import_playlist_id: Option<String>,
+    audio_files: Vec<AudioFile>,
+    path_patterns: Vec<String>,
+    match_settings: MatchSettings,
+    out_tracks: Rc<HashMap<String, Rc<Vec<(Similarity, Track)>>>>,
     id_mapping: Rc<HashMap<String, String>>,
+    // input ids whose Spotify track was chosen by the user
+    manual_mappings: HashSet<String>,
+    // the search query that first found an output track for an input track
+    out_track_queries: HashMap<(String, String), String>,
     out_playlists: Vec<SpotifyPlaylist>,
+    // playlists that can be exported, but not imported into
+    followed_playlists: Vec<SpotifyPlaylist>,
     selected_out_playlist: Option<String>,
     fetch_out_tracks_queue: VecDeque<(String, String, FetchInitiator)>,
     fetch_out_tracks_remaining: HashMap<String, String>,
-    fetch_out_tracks_remaining_batch_index: usize,
     import_matched_batch_index: usize,
     import_matched_done: bool,
+    export_format: ExportFormat,
+    export_file_name: String,
+    report_format: ReportFormat,
+    summary_format: SummaryFormat,
+    spotify_export_playlist: Option<String>,
+    spotify_export_format: ExportFormat,
+    // the tracks of the exported playlist while its pages are loaded
+    spotify_export_tracks: Option<Vec<Track>>,
     error_message: Option<String>,
 }
 
@@ -65,13 +156,37 @@ pub enum Msg {
     OutPlaylistsLoaded(Vec<SpotifyPlaylist>),
     OutPlaylistSelected(String),
     OutPlaylistCreated(SpotifyPlaylist),
-    InPlaylistSelected(File),
-    InPlaylistLoaded(FileData),
+    InPlaylistsSelected(Vec<File>),
+    InPlaylistLoaded(usize, FileData),
+    AudioFilesSelected(Vec<File>),
+    AudioChunkLoaded(usize, AudioChunk, Vec<u8>),
+    InPlaylistFormatSelected(usize, PlaylistFormat),
+    MergeModeSelected(MergeMode),
+    BatchModeToggled,
+    JobSelected(usize),
+    JobTargetSelected(usize, String),
+    JobPlaylistCreated(usize, SpotifyPlaylist),
+    ImportJobs,
+    LibraryPlaylistToggled(usize),
+    LibraryPlaylistsChosen,
+    CsvMapped(Box<Playlist>),
+    PathPatternsChanged(String),
+    MatchSettingsChanged(MatchSettings),
     SetIdMapping(String, Option<String>),
-    OutTracksFound(String, Vec<Track>, FetchInitiator),
-    RemainingOutTracksFound(Vec<(String, Track)>),
+    OutTracksFound(String, String, Vec<Track>, FetchInitiator),
+    RemainingOutTracksFound(Vec<String>, Vec<(String, Track)>),
     QueryOutTrack(String, String),
     ExportUnmatched,
+    ExportFormatSelected(ExportFormat),
+    ExportFileNameChanged(String),
+    ExportReport,
+    ReportFormatSelected(ReportFormat),
+    ExportSummary,
+    SummaryFormatSelected(SummaryFormat),
+    SpotifyExportPlaylistSelected(String),
+    SpotifyExportFormatSelected(ExportFormat),
+    ExportSpotifyPlaylist,
+    SpotifyExportPageLoaded(Vec<Track>, Option<String>),
     ImportMatched,
     ImportMatchedDone,
     SetError(String),
@@ -91,17 +206,63 @@ impl Component for Import {
                 Rc::new(HashMap::new())
             }
         };
+        let manual_mappings = {
+            if let Json(Ok(restored_manual_mappings)) = storage.restore(LS_MANUAL_MAPPINGS) {
+                restored_manual_mappings
+            } else {
+                HashSet::new()
+            }
+        };
+        let path_patterns = {
+            if let Json(Ok(restored_path_patterns)) = storage.restore(LS_PATH_PATTERNS) {
+                restored_path_patterns
+            } else {
+                DEFAULT_PATH_PATTERNS
+                    .iter()
+                    .map(|pattern| pattern.to_string())
+                    .collect()
+            }
+        };
+        let match_settings = {
+            if let Json(Ok(restored_match_settings)) = storage.restore(LS_MATCH_SETTINGS) {
+                restored_match_settings
+            } else {
+                MatchSettings::default()
+            }
+        };
         let state = State {
             in_tracks: Rc::new(Vec::new()),
+            in_title: None,
+            in_files: Vec::new(),
+            loading_in_files: Vec::new(),
+            selected_library_playlists: Vec::new(),
+            merge_mode: MergeMode::Concatenate,
+            batch_mode: false,
+            jobs: Vec::new(),
+            active_job: 0,
+            importing_job: None,
+            import_playlist_id: None,
+            audio_files: Vec::new(),
+            path_patterns,
+            match_settings,
             out_tracks: Rc::new(HashMap::new()),
             id_mapping,
+            manual_mappings,
+            out_track_queries: HashMap::new(),
             out_playlists: Vec::new(),
+            followed_playlists: Vec::new(),
             selected_out_playlist: None,
             fetch_out_tracks_queue: VecDeque::new(),
             fetch_out_tracks_remaining: HashMap::new(),
-            fetch_out_tracks_remaining_batch_index: 0,
             import_matched_batch_index: 0,
             import_matched_done: false,
+            export_format: ExportFormat::Xspf,
+            export_file_name: DEFAULT_EXPORT_FILE_NAME.to_string(),
+            report_format: ReportFormat::Csv,
+            summary_format: SummaryFormat::Html,
+            spotify_export_playlist: None,
+            spotify_export_format: ExportFormat::Xspf,
+            spotify_export_tracks: None,
             error_message: None,
         };
         let _interval_task = IntervalService::spawn(Duration::from_secs(60), link.callback(|_| Msg::Noop));
@@ -113,6 +274,7 @@ impl Component for Import {
             state,
             fetch_tasks: Vec::new(),
             reader_tasks: Vec::new(),
+            audio_reader_tasks: Vec::new(),
             _interval_task,
         };
         import.get_playlists();
@@ -123,7 +285,13 @@ impl Component for Import {
         match msg {
             Msg::OutPlaylistsLoaded(playlists) => {
                 self.state.error_message = None;
-                self.state.out_playlists = playlists;
+                let spotify_user_id = &self.props.spotify_user.user_id;
+                let (out_playlists, followed_playlists) =
+                    playlists.into_iter().partition(|playlist| {
+                        playlist.owner.id == *spotify_user_id || playlist.collaborative
+                    });
+                self.state.out_playlists = out_playlists;
+                self.state.followed_playlists = followed_playlists;
             }
             Msg::OutPlaylistSelected(playlist_id) => {
                 if playlist_id == "create" {
@@ -132,7 +300,7 @@ impl Component for Import {
                         .prompt_with_message("Please enter a name for the playlist:")
                         .expect("prompt not available")
                     {
-                        self.create_playlist(name);
+                        self.create_playlist(name, Msg::OutPlaylistCreated);
                     }
                 } else if !playlist_id.is_empty() {
                     self.state.selected_out_playlist = Some(playlist_id);
@@ -144,56 +312,191 @@ impl Component for Import {
                 self.state.selected_out_playlist = Some(playlist.id.clone());
                 self.state.out_playlists.push(playlist);
             }
-            Msg::InPlaylistSelected(file) => {
-                let callback = self.link.callback(Msg::InPlaylistLoaded);
-                let reader_task = self.reader.read_file(file, callback).unwrap();
-                self.reader_tasks.push(reader_task);
-            }
-            Msg::InPlaylistLoaded(file_data) => {
-                let playlist: Playlist = serde_xml_rs::from_reader(&file_data.content[..])
-                    .expect("deserialize playlist"); // TODO error handling
-                self.state.in_tracks = Rc::new(
-                    playlist
-                        .track_list
-                        .tracks
-                        .into_iter()
-                        .map(Rc::new)
-                        .collect(),
-                );
-
-                for in_track in self.state.in_tracks.iter() {
-                    self.state.fetch_out_tracks_queue.push_back((
-                        in_track.id(),
-                        in_track.query(),
-                        FetchInitiator::Auto(1),
-                    ));
+            Msg::InPlaylistsSelected(files) => {
+                self.state.loading_in_files = vec![None; files.len()];
+                for (index, file) in files.into_iter().enumerate() {
+                    let callback = self
+                        .link
+                        .callback(move |file_data| Msg::InPlaylistLoaded(index, file_data));
+                    let reader_task = self.reader.read_file(file, callback).unwrap();
+                    self.reader_tasks.push(reader_task);
                 }
-
-                self.state.fetch_out_tracks_remaining.clear();
-                self.state.fetch_out_tracks_remaining_batch_index = 0;
-
-                for in_track in self.state.in_tracks.iter() {
-                    let input_id = in_track.id();
-                    if let Some(output_id) = self.state.id_mapping.get(&input_id) {
-                        if !self.state.out_tracks.contains_key(output_id) {
-                            self.state
-                                .fetch_out_tracks_remaining
-                                .insert(input_id, output_id.clone());
-                        }
+            }
+            Msg::InPlaylistLoaded(index, file_data) => {
+                self.reader_tasks.retain(Task::is_active);
+                if let Some(loading_in_file) = self.state.loading_in_files.get_mut(index) {
+                    *loading_in_file = Some(file_data);
+                }
+                // the files are only replaced once all of them have been read
+                if self.state.loading_in_files.iter().all(Option::is_some) {
+                    self.state.in_files = self
+                        .state
+                        .loading_in_files
+                        .drain(..)
+                        .flatten()
+                        .map(|file_data| {
+                            let detection = detect_format(&file_data.name, &file_data.content);
+                            let (content, skipped_tracks) =
+                                parse_in_file(&file_data, detection.format);
+                            InFile {
+                                data: file_data,
+                                detection,
+                                content,
+                                skipped_tracks,
+                            }
+                        })
+                        .collect();
+                    self.state.selected_library_playlists.clear();
+                    self.load_in_files();
+                }
+            }
+            Msg::AudioFilesSelected(files) => {
+                self.read_audio_files(files);
+            }
+            Msg::AudioChunkLoaded(index, chunk, content) => {
+                self.audio_reader_tasks.retain(Task::is_active);
+                if let Some(audio_file) = self.state.audio_files.get_mut(index) {
+                    // a read that failed ends without any content
+                    if content.is_empty() && audio_file.size > 0 {
+                        audio_file.failed = true;
+                    }
+                    match chunk {
+                        AudioChunk::Head => audio_file.head = Some(content),
+                        AudioChunk::Tail => audio_file.tail = Some(content),
+                    }
+                    // the tags are read as soon as both chunks of the file have arrived
+                    let complete = !audio_file.failed
+                        && audio_file.head.is_some()
+                        && (audio_file.tail.is_some() || audio_file.size <= HEAD_SIZE + TAIL_SIZE);
+                    if complete {
+                        let head = audio_file.head.take().unwrap_or_default();
+                        let tail = audio_file.tail.take();
+                        audio_file.tags = Some(read_audio_tags(
+                            &head,
+                            tail.as_ref().unwrap_or(&head),
+                            audio_file.size,
+                        ));
                     }
                 }
-
-                self.fetch_next_out_track();
+                self.load_audio_files();
+            }
+            Msg::InPlaylistFormatSelected(index, format) => {
+                if let Some(in_file) = self.state.in_files.get_mut(index) {
+                    let (content, skipped_tracks) = parse_in_file(&in_file.data, format);
+                    in_file.detection.format = format;
+                    in_file.content = content;
+                    in_file.skipped_tracks = skipped_tracks;
+                }
+                self.state.selected_library_playlists.clear();
+                self.load_in_files();
+            }
+            Msg::MergeModeSelected(merge_mode) => {
+                self.state.merge_mode = merge_mode;
+                self.load_in_files();
+            }
+            Msg::BatchModeToggled => {
+                self.state.batch_mode = !self.state.batch_mode;
+                self.load_in_files();
+            }
+            Msg::JobSelected(index) => {
+                self.state.active_job = index;
+            }
+            Msg::JobTargetSelected(index, playlist_id) => {
+                if let Some(job) = self.state.jobs.get_mut(index) {
+                    job.target = Some(playlist_id).filter(|playlist_id| !playlist_id.is_empty());
+                }
+            }
+            Msg::JobPlaylistCreated(index, playlist) => {
+                if let Some(job) = self.state.jobs.get_mut(index) {
+                    job.target = Some(playlist.id.clone());
+                }
+                // the import might have been stopped in the meantime
+                if self.state.importing_job == Some(index) {
+                    self.state.import_playlist_id = Some(playlist.id.clone());
+                    self.import_next_batch();
+                }
+                self.state.out_playlists.push(playlist);
+            }
+            Msg::ImportJobs => {
+                for job in self.state.jobs.iter_mut() {
+                    if job.status == JobStatus::Failed {
+                        job.status = JobStatus::Ready;
+                    }
+                }
+                self.state.import_matched_done = false;
+                self.import_next_job();
+            }
+            Msg::CsvMapped(playlist) => {
+                if let Some(in_file) = self
+                    .state
+                    .in_files
+                    .iter_mut()
+                    .find(|in_file| matches!(in_file.content, InFileContent::Table(_)))
+                {
+                    in_file.content = InFileContent::Playlists(vec![*playlist]);
+                }
+                self.load_in_files();
+            }
+            Msg::PathPatternsChanged(path_patterns) => {
+                self.state.path_patterns = path_patterns
+                    .lines()
+                    .map(str::trim)
+                    .filter(|pattern| !pattern.is_empty())
+                    .map(ToOwned::to_owned)
+                    .collect();
+                self.storage
+                    .store(LS_PATH_PATTERNS, Json(&self.state.path_patterns));
+                self.apply_path_patterns();
+            }
+            Msg::MatchSettingsChanged(match_settings) => {
+                self.state.match_settings = match_settings;
+                self.storage
+                    .store(LS_MATCH_SETTINGS, Json(&self.state.match_settings));
+                self.rescore_out_tracks();
+            }
+            Msg::LibraryPlaylistToggled(index) => {
+                let selected = &mut self.state.selected_library_playlists;
+                if let Some(position) = selected.iter().position(|selected| *selected == index) {
+                    selected.remove(position);
+                } else {
+                    selected.push(index);
+                }
+            }
+            Msg::LibraryPlaylistsChosen => {
+                let selected_library_playlists = &self.state.selected_library_playlists;
+                if let Some(in_file) = self
+                    .state
+                    .in_files
+                    .iter_mut()
+                    .find(|in_file| matches!(in_file.content, InFileContent::Library(_)))
+                {
+                    if let InFileContent::Library(library) = &in_file.content {
+                        // each library playlist is a source of its own
+                        let playlists = selected_library_playlists
+                            .iter()
+                            .map(|index| library.to_playlist(&[*index]))
+                            .collect();
+                        in_file.content = InFileContent::Playlists(playlists);
+                    }
+                }
+                self.state.selected_library_playlists.clear();
+                self.load_in_files();
             }
             Msg::SetIdMapping(input_id, Some(output_id)) => {
+                self.state.manual_mappings.insert(input_id.clone());
                 Rc::make_mut(&mut self.state.id_mapping).insert(input_id, output_id);
                 self.storage
                     .store(LS_ID_MAPPING, Json(&*self.state.id_mapping));
+                self.storage
+                    .store(LS_MANUAL_MAPPINGS, Json(&self.state.manual_mappings));
             }
             Msg::SetIdMapping(input_id, None) => {
+                self.state.manual_mappings.remove(&input_id);
                 Rc::make_mut(&mut self.state.id_mapping).remove(&input_id);
                 self.storage
                     .store(LS_ID_MAPPING, Json(&*self.state.id_mapping));
+                self.storage
+                    .store(LS_MANUAL_MAPPINGS, Json(&self.state.manual_mappings));
             }
             Msg::QueryOutTrack(input_id, query) => {
                 self.state.fetch_out_tracks_queue.push_back((
@@ -203,28 +506,25 @@ impl Component for Import {
                 ));
                 self.fetch_next_out_track();
             }
-            Msg::OutTracksFound(input_id, new_out_tracks, fetch_initiator) => {
+            Msg::OutTracksFound(input_id, query, new_out_tracks, fetch_initiator) => {
                 self.state.error_message = None;
 
                 if new_out_tracks.len() > 0 {
-                    self.insert_out_track(input_id, new_out_tracks);
+                    self.insert_out_track(input_id, new_out_tracks, Some(query));
                 } else {
                     if let FetchInitiator::Auto(fetch_try) = fetch_initiator {
-                        if fetch_try == 1 {
-                            let in_track = self
-                                .state
-                                .in_tracks
-                                .iter()
-                                .find(|in_track| in_track.id() == *input_id)
-                                .expect("received track for invalid input");
-
-                            let query = in_track.query();
-                            let adjusted_query = in_track.adjusted_query();
-
-                            if adjusted_query != query {
+                        // the input playlist might have been replaced in the meantime
+                        if let Some(in_track) = self
+                            .state
+                            .in_tracks
+                            .iter()
+                            .find(|in_track| in_track.id() == *input_id)
+                        {
+                            // the next, less precise query
+                            if let Some(query) = search_queries(in_track).get(fetch_try) {
                                 self.state.fetch_out_tracks_queue.push_back((
                                     in_track.id(),
-                                    adjusted_query,
+                                    query.clone(),
                                     FetchInitiator::Auto(fetch_try + 1),
                                 ));
                             }
@@ -234,11 +534,16 @@ impl Component for Import {
 
                 self.fetch_next_out_track();
             }
-            Msg::RemainingOutTracksFound(tracks) => {
+            Msg::RemainingOutTracksFound(input_ids, tracks) => {
                 self.state.error_message = None;
 
                 for (input_id, new_out_track) in tracks {
-                    self.insert_out_track(input_id, vec![new_out_track]);
+                    self.insert_out_track(input_id, vec![new_out_track], None);
+                }
+
+                // tracks that Spotify doesn't know anymore are not requested again
+                for input_id in input_ids {
+                    self.state.fetch_out_tracks_remaining.remove(&input_id);
                 }
 
                 self.fetch_next_out_track();
@@ -253,26 +558,108 @@ impl Component for Import {
                     .collect::<Vec<_>>();
                 let playlist = Playlist::with_tracks_and_title(
                     tracks,
-                    "spotify-playlist-importer".to_string(),
+                    self.state
+                        .in_title
+                        .clone()
+                        .unwrap_or_else(|| DEFAULT_EXPORT_FILE_NAME.to_string()),
                 );
-                let xspf = playlist.to_xspf();
+                let format = self.state.export_format;
+                let content = playlist.export(format);
+                let file_name = export_file_name(&self.state.export_file_name, format.extension());
                 unsafe {
-                    download_file("spotify-playlist-importer.xspf", &xspf);
+                    download_file(&file_name, &content);
+                }
+            }
+            Msg::ExportFormatSelected(format) => {
+                self.state.export_format = format;
+            }
+            Msg::ExportFileNameChanged(file_name) => {
+                self.state.export_file_name = file_name;
+            }
+            Msg::ExportReport => {
+                let rows = self
+                    .state
+                    .in_tracks
+                    .iter()
+                    .map(|in_track| self.report_row(in_track))
+                    .collect::<Vec<_>>();
+                let format = self.state.report_format;
+                let content = write_report(&rows, format);
+                let file_name = export_file_name(
+                    &format!("{} report", self.state.export_file_name),
+                    format.extension(),
+                );
+                unsafe {
+                    download_file(&file_name, &content);
+                }
+            }
+            Msg::ReportFormatSelected(format) => {
+                self.state.report_format = format;
+            }
+            Msg::ExportSummary => {
+                let summary = self.summary();
+                let format = self.state.summary_format;
+                let content = summary.export(format);
+                let file_name = export_file_name(
+                    &format!("{} summary", self.state.export_file_name),
+                    format.extension(),
+                );
+                unsafe {
+                    download_file(&file_name, &content);
+                }
+            }
+            Msg::SummaryFormatSelected(format) => {
+                self.state.summary_format = format;
+            }
+            Msg::SpotifyExportPlaylistSelected(playlist_id) => {
+                self.state.spotify_export_playlist =
+                    Some(playlist_id).filter(|playlist_id| !playlist_id.is_empty());
+            }
+            Msg::SpotifyExportFormatSelected(format) => {
+                self.state.spotify_export_format = format;
+            }
+            Msg::ExportSpotifyPlaylist => {
+                if let Some(playlist_id) = self.state.spotify_export_playlist.clone() {
+                    self.state.spotify_export_tracks = Some(Vec::new());
+                    self.fetch_spotify_export_page(format!(
+                        "https://api.spotify.com/v1/playlists/{}/tracks?limit=100",
+                        playlist_id
+                    ));
+                }
+            }
+            Msg::SpotifyExportPageLoaded(mut tracks, next) => {
+                self.state.error_message = None;
+                if let Some(exported_tracks) = self.state.spotify_export_tracks.as_mut() {
+                    exported_tracks.append(&mut tracks);
+                    match next {
+                        Some(next) => self.fetch_spotify_export_page(next),
+                        None => self.download_spotify_export(),
+                    }
                 }
             }
             Msg::ImportMatched => {
                 if let Some(playlist_id) = self.state.selected_out_playlist.clone() {
+                    self.state.importing_job = None;
+                    self.state.import_playlist_id = Some(playlist_id);
                     self.state.import_matched_batch_index = 0;
                     self.state.import_matched_done = false;
-                    self.add_next_to_playlist(&playlist_id);
+                    self.import_next_batch();
                 }
             }
             Msg::ImportMatchedDone => {
                 self.state.error_message = None;
-                self.state.import_matched_done = true;
+                self.import_next_batch();
             }
             Msg::SetError(error_message) => {
                 self.state.error_message = Some(error_message);
+                self.state.spotify_export_tracks = None;
+                // stop the batch import, it can be resumed from the failed job
+                if let Some(index) = self.state.importing_job.take() {
+                    self.state.import_playlist_id = None;
+                    if let Some(job) = self.state.jobs.get_mut(index) {
+                        job.status = JobStatus::Failed;
+                    }
+                }
             }
             Msg::Noop => {}
         }
@@ -288,22 +675,91 @@ impl Component for Import {
         let onchange_in_playlist = self
             .link
             .callback(|event: yew::html::ChangeData| match event {
-                yew::html::ChangeData::Files(files) => match files.get(0) {
-                    Some(file) => Msg::InPlaylistSelected(file),
-                    None => Msg::Noop,
-                },
+                yew::html::ChangeData::Files(files) => Msg::InPlaylistsSelected(
+                    (0..files.length())
+                        .filter_map(|index| files.get(index))
+                        .collect(),
+                ),
+                _ => Msg::Noop,
+            });
+        let onchange_audio_files = self
+            .link
+            .callback(|event: yew::html::ChangeData| match event {
+                yew::html::ChangeData::Files(files) => Msg::AudioFilesSelected(
+                    (0..files.length())
+                        .filter_map(|index| files.get(index))
+                        .collect(),
+                ),
                 _ => Msg::Noop,
             });
-        let onchange_out_playlist =
+        let ondragover = self.link.callback(|event: DragEvent| {
+            // allows dropping
+            event.prevent_default();
+            Msg::Noop
+        });
+        let ondrop = self.link.callback(|event: DragEvent| {
+            event.prevent_default();
+            let files = event
+                .data_transfer()
+                .and_then(|data_transfer| data_transfer.files())
+                .map(|files| {
+                    (0..files.length())
+                        .filter_map(|index| files.get(index))
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+            if files.is_empty() {
+                Msg::Noop
+            } else if files.iter().any(|file| is_audio_file(&file.name())) {
+                Msg::AudioFilesSelected(files)
+            } else {
+                Msg::InPlaylistsSelected(files)
+            }
+        });
+        let onchange_path_patterns =
+            self.link
+                .callback(|event: yew::html::ChangeData| match event {
+                    yew::html::ChangeData::Value(value) => Msg::PathPatternsChanged(value),
+                    _ => Msg::Noop,
+                });
+        let onclick_export_unmatched = self.link.callback(|_| Msg::ExportUnmatched);
+        let onchange_export_format =
             self.link
                 .callback(|event: yew::html::ChangeData| match event {
                     yew::html::ChangeData::Select(select) => {
-                        Msg::OutPlaylistSelected(select.value())
+                        ExportFormat::from_str(&select.value())
+                            .map_or(Msg::Noop, Msg::ExportFormatSelected)
                     }
                     _ => Msg::Noop,
                 });
-        let onclick_import_matched = self.link.callback(|_| Msg::ImportMatched);
-        let onclick_export_unmatched = self.link.callback(|_| Msg::ExportUnmatched);
+        let onclick_export_report = self.link.callback(|_| Msg::ExportReport);
+        let onchange_report_format =
+            self.link
+                .callback(|event: yew::html::ChangeData| match event {
+                    yew::html::ChangeData::Select(select) => {
+                        ReportFormat::from_str(&select.value())
+                            .map_or(Msg::Noop, Msg::ReportFormatSelected)
+                    }
+                    _ => Msg::Noop,
+                });
+        let onclick_export_summary = self.link.callback(|_| Msg::ExportSummary);
+        let onchange_summary_format =
+            self.link
+                .callback(|event: yew::html::ChangeData| match event {
+                    yew::html::ChangeData::Select(select) => {
+                        SummaryFormat::from_str(&select.value())
+                            .map_or(Msg::Noop, Msg::SummaryFormatSelected)
+                    }
+                    _ => Msg::Noop,
+                });
+        let onchange_export_file_name =
+            self.link
+                .callback(|event: yew::html::ChangeData| match event {
+                    yew::html::ChangeData::Value(value) => Msg::ExportFileNameChanged(value),
+                    _ => Msg::Noop,
+                });
+
+        let onchange_match_settings = self.link.callback(Msg::MatchSettingsChanged);
 
         let onmappingchange = self
             .link
@@ -318,7 +774,142 @@ impl Component for Import {
             html! {}
         };
 
-        let render_is_loading = if self.fetch_tasks.iter().any(FetchTask::is_active) {
+        let render_in_files = html! {
+            <div class="in-files">
+                {
+                    for self.state.in_files.iter().enumerate().map(|(index, in_file)| {
+                        self.view_in_file(index, in_file)
+                    })
+                }
+            </div>
+        };
+
+        let loaded_in_files = self
+            .state
+            .in_files
+            .iter()
+            .map(|in_file| match &in_file.content {
+                InFileContent::Playlists(playlists) => playlists.len(),
+                _ => 0,
+            })
+            .sum::<usize>();
+        let onclick_batch_mode = self.link.callback(|_| Msg::BatchModeToggled);
+        // the jobs would be rebuilt while one of them is imported
+        let importing_job = self
+            .state
+            .jobs
+            .iter()
+            .any(|job| job.status == JobStatus::Importing);
+        let render_batch_mode = if loaded_in_files > 1 || self.state.batch_mode {
+            html! {
+                <div>
+                    <span class="form">{"Batch mode:"}</span>
+                    <label class="inline">
+                        <input type="checkbox" checked=self.state.batch_mode disabled=importing_job onclick=onclick_batch_mode/>
+                        {"Import every playlist into a Spotify playlist of its own"}
+                    </label>
+                </div>
+            }
+        } else {
+            html! {}
+        };
+
+        let render_merge_mode = if loaded_in_files > 1 && !self.state.batch_mode {
+            let onchange_merge_mode =
+                self.link
+                    .callback(|event: yew::html::ChangeData| match event {
+                        yew::html::ChangeData::Select(select) => {
+                            MergeMode::from_str(&select.value())
+                                .map_or(Msg::Noop, Msg::MergeModeSelected)
+                        }
+                        _ => Msg::Noop,
+                    });
+            html! {
+                <div>
+                    <span class="form">{"Merge playlists:"}</span>
+                    <select class="inline" disabled=importing_job onchange=onchange_merge_mode>
+                        {
+                            for MergeMode::iter().map(|merge_mode| html! {
+                                <option value={merge_mode.to_string()} selected={merge_mode == self.state.merge_mode}>
+                                    {merge_mode.to_string()}
+                                </option>
+                            })
+                        }
+                    </select>
+                </div>
+            }
+        } else {
+            html! {}
+        };
+
+        // libraries and tables need more input, one file after the other
+        let pending_library =
+            self.state
+                .in_files
+                .iter()
+                .find_map(|in_file| match &in_file.content {
+                    InFileContent::Library(library) => Some(library),
+                    _ => None,
+                });
+        let render_library = if let Some(library) = pending_library {
+            let onclick_choose_library_playlists =
+                self.link.callback(|_| Msg::LibraryPlaylistsChosen);
+            html! {
+                <div>
+                    <br/>
+                    <span class="form">{"Library playlists:"}</span>
+                    <div class="library-playlists">
+                        {
+                            for library.playlists.iter().enumerate().map(|(index, playlist)| {
+                                let checked = self.state.selected_library_playlists.contains(&index);
+                                let onclick = self.link.callback(move |_| Msg::LibraryPlaylistToggled(index));
+                                html! {
+                                    <label>
+                                        <input type="checkbox" checked=checked onclick=onclick/>
+                                        {format!("{} ({} tracks)", playlist.name, playlist.track_ids.len())}
+                                    </label>
+                                }
+                            })
+                        }
+                    </div>
+                    <button
+                        onclick=onclick_choose_library_playlists
+                        disabled=self.state.selected_library_playlists.is_empty()
+                    >
+                        {"Load playlists"}
+                    </button>
+                </div>
+            }
+        } else {
+            html! {}
+        };
+
+        let pending_table = self
+            .state
+            .in_files
+            .iter()
+            .find_map(|in_file| match &in_file.content {
+                InFileContent::Table(content) => Some((content, &in_file.data.name)),
+                _ => None,
+            });
+        let render_csv_mapping = if let Some((content, title)) = pending_table {
+            html! {
+                <div>
+                    <br/>
+                    <CsvMapping
+                        content=content.clone()
+                        title=title.clone()
+                        onconfirm=self.link.callback(|playlist| Msg::CsvMapped(Box::new(playlist)))
+                    />
+                </div>
+            }
+        } else {
+            html! {}
+        };
+
+        let render_is_loading = if self.fetch_tasks.iter().any(FetchTask::is_active)
+            || !self.state.audio_files.is_empty()
+        {
             html! {<div class="inline lds-dual-ring"/>}
         } else {
             html! {}
@@ -330,6 +921,69 @@ impl Component for Import {
             html! {}
         };
 
+        // in batch mode the table shows the songs of the selected job
+        let (in_tracks, render_jobs) = match self.state.jobs.get(self.state.active_job) {
+            Some(job) => (
+                Rc::new(self.state.in_tracks[job.tracks.clone()].to_vec()),
+                self.view_jobs(),
+            ),
+            None => (self.state.in_tracks.clone(), html! {}),
+        };
+
+        // batch mode has its own targets and import button
+        let (render_out_playlist, render_import_matched) = if self.state.jobs.is_empty() {
+            let onchange_out_playlist =
+                self.link
+                    .callback(|event: yew::html::ChangeData| match event {
+                        yew::html::ChangeData::Select(select) => {
+                            Msg::OutPlaylistSelected(select.value())
+                        }
+                        _ => Msg::Noop,
+                    });
+            let onclick_import_matched = self.link.callback(|_| Msg::ImportMatched);
+            (
+                html! {
+                    <>
+                        <br/>
+                        <div>
+                            <span class="form">{"Spotify playlist:"}</span>
+                            <select onchange=onchange_out_playlist class="inline playlist">
+                                {
+                                    if self.state.selected_out_playlist.is_none() {
+                                        html! {<option value={""}>{"Select playlist"}</option>}
+                                    } else {
+                                        html! {}
+                                    }
+                                }
+                                {
+                                    for self.state.out_playlists.iter().map(|playlist| {
+                                        let value = &playlist.id;
+                                        let text = &playlist.name;
+                                        let selected = self.state.selected_out_playlist.as_ref().map_or(false, |selected_out_playlist| selected_out_playlist == &playlist.id);
+                                        html! {
+                                            <option value={value} selected={selected}>{text}</option>
+                                        }
+                                    })
+                                }
+                                <option value={"create"}>{"Create new playlist..."}</option>
+                            </select>
+                        </div>
+                    </>
+                },
+                html! {
+                    <button
+                        class="main"
+                        onclick=onclick_import_matched
+                        disabled=self.state.in_tracks.is_empty() || self.state.selected_out_playlist.is_none()
+                    >
+                        {"Import playlist"}
+                    </button>
+                },
+            )
+        } else {
+            (html! {}, html! {})
+        };
+
         let render_message = if self.state.import_matched_done {
             html! {<div class="inline success">{"✔ Import succeeded"}</div>}
         } else {
@@ -337,64 +991,112 @@ impl Component for Import {
         };
 
         html! {
-            <div>
+            <div ondragover=ondragover ondrop=ondrop>
                 {render_error_message}
                 <div>
-                    <span class="form">{"Input playlist:"}</span>
-                    <input class="inline" type="file" onchange=onchange_in_playlist/>
+                    <span class="form">{"Input playlists:"}</span>
+                    <input class="inline" type="file" multiple=true onchange=onchange_in_playlist/>
                     {render_is_loading}
                 </div>
+                {render_in_files}
+                {render_batch_mode}
+                {render_merge_mode}
+                <div>
+                    <span class="form">{"Music folder:"}</span>
+                    <input class="inline" type="file" multiple=true webkitdirectory="" onchange=onchange_audio_files/>
+                    <span class="inline status">{"or drop audio files onto the page"}</span>
+                </div>
+                <div>
+                    <span class="form">{"Path patterns:"}</span>
+                    <textarea
+                        class="inline path-patterns"
+                        rows=self.state.path_patterns.len().max(1).to_string()
+                        value=self.state.path_patterns.join("\n")
+                        onchange=onchange_path_patterns
+                    />
+                    <span class="inline status">
+                        {"Fill in missing fields from the file location, e.g. {artist}/{album}/{track} - {title}"}
+                    </span>
+                </div>
+                <MatchSettingsPanel
+                    settings=self.state.match_settings
+                    onchange=onchange_match_settings
+                />
+                {render_library}
+                {render_csv_mapping}
                 <br/>
+                {render_jobs}
                 <TrackList
-                    in_tracks=self.state.in_tracks.clone()
+                    in_tracks=in_tracks
                     out_tracks=self.state.out_tracks.clone()
                     id_mapping=self.state.id_mapping.clone()
                     onmappingchange=onmappingchange
                     onquerytrack=onquerytrack
                 />
+                {render_out_playlist}
                 <br/>
                 <div>
-                    <span class="form">{"Spotify playlist:"}</span>
-                    <select onchange=onchange_out_playlist class="inline playlist">
-                        {
-                            if self.state.selected_out_playlist.is_none() {
-                                html! {<option value={""}>{"Select playlist"}</option>}
-                            } else {
-                                html! {}
-                            }
-                        }
+                    {render_import_matched}
+                    <button
+                        onclick=onclick_export_unmatched
+                        disabled=self.state.in_tracks.is_empty()
+                    >
+                        {"Export unmatched"}
+                    </button>
+                    <select onchange=onchange_export_format>
                         {
-                            for self.state.out_playlists.iter().map(|playlist| {
-                                let value = &playlist.id;
-                                let text = &playlist.name;
-                                let selected = self.state.selected_out_playlist.as_ref().map_or(false, |selected_out_playlist| selected_out_playlist == &playlist.id);
-                                html! {
-                                    <option value={value} selected={selected}>{text}</option>
-                                }
+                            for ExportFormat::iter().map(|format| html! {
+                                <option value={format.to_string()} selected={format == self.state.export_format}>
+                                    {format.to_string()}
+                                </option>
                             })
                         }
-                        <option value={"create"}>{"Create new playlist..."}</option>
                     </select>
-                </div>
-                <br/>
-                <div>
+                    <input
+                        type="text"
+                        class="file-name"
+                        value=self.state.export_file_name.clone()
+                        onchange=onchange_export_file_name
+                    />
+                    {format!(".{}", self.state.export_format.extension())}
                     <button
-                        class="main"
-                        onclick=onclick_import_matched
-                        disabled=self.state.in_tracks.is_empty() || self.state.selected_out_playlist.is_none()
+                        class="export-report"
+                        onclick=onclick_export_report
+                        disabled=self.state.in_tracks.is_empty()
                     >
-                        {"Import playlist"}
+                        {"Export match report"}
                     </button>
+                    <select onchange=onchange_report_format>
+                        {
+                            for ReportFormat::iter().map(|format| html! {
+                                <option value={format.to_string()} selected={format == self.state.report_format}>
+                                    {format.to_string()}
+                                </option>
+                            })
+                        }
+                    </select>
                     <button
-                        onclick=onclick_export_unmatched
+                        class="export-report"
+                        onclick=onclick_export_summary
                         disabled=self.state.in_tracks.is_empty()
                     >
-                        {"Export unmatched"}
+                        {"Export summary"}
                     </button>
+                    <select onchange=onchange_summary_format>
+                        {
+                            for SummaryFormat::iter().map(|format| html! {
+                                <option value={format.to_string()} selected={format == self.state.summary_format}>
+                                    {format.to_string()}
+                                </option>
+                            })
+                        }
+                    </select>
                     {render_message}
                     {render_is_submitting}
                 </div>
                 <br/>
+                {self.view_spotify_export()}
+                <br/>
                 <div class="status">
                     {format!("Your Spotify session will expire in {} minutes", self.props.spotify_user.expiration_timeout() / 1_000 / 60)}
                 </div>
@@ -404,18 +1106,530 @@ impl Component for Import {
 }
 
 impl Import {
-    fn insert_out_track(&mut self, input_id: String, new_out_tracks: Vec<Track>) {
-        let in_track = self
+    fn view_in_file(&self, index: usize, in_file: &InFile) -> Html {
+        let onchange_in_format =
+            self.link
+                .callback(move |event: yew::html::ChangeData| match event {
+                    yew::html::ChangeData::Select(select) => {
+                        PlaylistFormat::from_str(&select.value()).map_or(Msg::Noop, |format| {
+                            Msg::InPlaylistFormatSelected(index, format)
+                        })
+                    }
+                    _ => Msg::Noop,
+                });
+
+        html! {
+            <div>
+                <span class="form">{&in_file.data.name}</span>
+                <select class="inline" onchange=onchange_in_format>
+                    {
+                        for PlaylistFormat::iter().map(|format| html! {
+                            <option value={format.to_string()} selected={format == in_file.detection.format}>
+                                {format.to_string()}
+                            </option>
+                        })
+                    }
+                </select>
+                {
+                    if in_file.detection.ambiguous {
+                        html! {<span class="inline warning">{"Format could not be detected reliably, please check"}</span>}
+                    } else {
+                        html! {}
+                    }
+                }
+                {
+                    if let InFileContent::Error(error) = &in_file.content {
+                        html! {<span class="inline warning">{format!("Could not read the file: {}", error)}</span>}
+                    } else {
+                        html! {}
+                    }
+                }
+                {
+                    if in_file.skipped_tracks.is_empty() {
+                        html! {}
+                    } else {
+                        html! {
+                            <div class="warning">
+                                {format!("{} entries could not be read:", in_file.skipped_tracks.len())}
+                                <ul>
+                                    {
+                                        for in_file.skipped_tracks.iter().map(|error| html! {
+                                            <li>{error.to_string()}</li>
+                                        })
+                                    }
+                                </ul>
+                            </div>
+                        }
+                    }
+                }
+            </div>
+        }
+    }
+
+    fn load_in_files(&mut self) {
+        let playlists = self
+            .state
+            .in_files
+            .iter()
+            .flat_map(|in_file| match &in_file.content {
+                InFileContent::Playlists(playlists) => playlists
+                    .iter()
+                    .map(|playlist| {
+                        (
+                            playlist
+                                .title
+                                .clone()
+                                .unwrap_or_else(|| in_file.data.name.clone()),
+                            playlist.track_list.tracks.clone(),
+                        )
+                    })
+                    .collect(),
+                _ => Vec::new(),
+            })
+            .collect::<Vec<_>>();
+        // keep the previously loaded tracks until one of the files could be read
+        if playlists.is_empty() {
+            return;
+        }
+
+        let title = playlists
+            .iter()
+            .map(|(source, _)| source.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let tracks = if self.state.batch_mode {
+            self.load_jobs(playlists)
+        } else {
+            self.clear_jobs();
+            merge_playlists(playlists, self.state.merge_mode)
+        };
+        self.load_in_playlist(Playlist::with_tracks_and_title(tracks, title));
+    }
+
+    // a job that is being imported can't be continued once the jobs are rebuilt
+    fn clear_jobs(&mut self) {
+        self.state.jobs.clear();
+        if self.state.importing_job.take().is_some() {
+            self.state.import_playlist_id = None;
+        }
+    }
+
+    // the tracks of all jobs are searched together, each job refers to its part of them
+    fn load_jobs(&mut self, playlists: Vec<(String, Vec<Track>)>) -> Vec<Track> {
+        self.clear_jobs();
+        let mut tracks = Vec::new();
+        self.state.jobs = playlists
+            .into_iter()
+            .map(|(title, playlist_tracks)| {
+                let start = tracks.len();
+                tracks.extend(playlist_tracks.into_iter().map(|mut track| {
+                    track.sources = vec![title.clone()];
+                    track
+                }));
+                // prefer an existing playlist with the same name
+                let target = self
+                    .state
+                    .out_playlists
+                    .iter()
+                    .find(|playlist| playlist.name == title)
+                    .map(|playlist| playlist.id.clone());
+                Job {
+                    title,
+                    tracks: start..tracks.len(),
+                    target,
+                    status: JobStatus::Ready,
+                }
+            })
+            .collect();
+        self.state.active_job = 0;
+        tracks
+    }
+
+    fn view_spotify_export(&self) -> Html {
+        let onchange_playlist = self
+            .link
+            .callback(|event: yew::html::ChangeData| match event {
+                yew::html::ChangeData::Select(select) => {
+                    Msg::SpotifyExportPlaylistSelected(select.value())
+                }
+                _ => Msg::Noop,
+            });
+        let onchange_format = self
+            .link
+            .callback(|event: yew::html::ChangeData| match event {
+                yew::html::ChangeData::Select(select) => ExportFormat::from_str(&select.value())
+                    .map_or(Msg::Noop, Msg::SpotifyExportFormatSelected),
+                _ => Msg::Noop,
+            });
+        let onclick_export = self.link.callback(|_| Msg::ExportSpotifyPlaylist);
+
+        let render_is_exporting = match &self.state.spotify_export_tracks {
+            Some(tracks) => html! {
+                <>
+                    <div class="inline lds-dual-ring"/>
+                    <span>{format!("{} tracks loaded", tracks.len())}</span>
+                </>
+            },
+            None => html! {},
+        };
+
+        html! {
+            <div>
+                <span class="form">{"Export from Spotify:"}</span>
+                <select onchange=onchange_playlist>
+                    <option value={""}>{"Select playlist"}</option>
+                    {
+                        for self.state.out_playlists.iter().chain(self.state.followed_playlists.iter()).map(|playlist| {
+                            let selected = Some(&playlist.id) == self.state.spotify_export_playlist.as_ref();
+                            html! {
+                                <option value={&playlist.id} selected={selected}>{&playlist.name}</option>
+                            }
+                        })
+                    }
+                </select>
+                <select onchange=onchange_format>
+                    {
+                        for ExportFormat::iter().map(|format| html! {
+                            <option value={format.to_string()} selected={format == self.state.spotify_export_format}>
+                                {format.to_string()}
+                            </option>
+                        })
+                    }
+                </select>
+                <button
+                    onclick=onclick_export
+                    disabled=self.state.spotify_export_playlist.is_none() || self.state.spotify_export_tracks.is_some()
+                >
+                    {"Export playlist"}
+                </button>
+                {render_is_exporting}
+            </div>
+        }
+    }
+
+    fn view_jobs(&self) -> Html {
+        let onclick_import_jobs = self.link.callback(|_| Msg::ImportJobs);
+
+        html! {
+            <div>
+                <table class="jobs">
+                    <thead>
+                        <tr>
+                            <th>{"Playlist"}</th>
+                            <th class="right">{"Songs"}</th>
+                            <th class="right">{"Matched"}</th>
+                            <th>{"Spotify playlist"}</th>
+                            <th>{"Status"}</th>
+                        </tr>
+                    </thead>
+                    <tbody>
+                        {
+                            for self.state.jobs.iter().enumerate().map(|(index, job)| {
+                                let onclick = self.link.callback(move |_| Msg::JobSelected(index));
+                                let onchange = self
+                                    .link
+                                    .callback(move |event: yew::html::ChangeData| match event {
+                                        yew::html::ChangeData::Select(select) => {
+                                            Msg::JobTargetSelected(index, select.value())
+                                        }
+                                        _ => Msg::Noop,
+                                    });
+                                let matched = self.state.in_tracks[job.tracks.clone()]
+                                    .iter()
+                                    .filter(|in_track| self.state.id_mapping.contains_key(&in_track.id()))
+                                    .count();
+                                let status = match job.status {
+                                    JobStatus::Ready => "",
+                                    JobStatus::Importing => "Importing...",
+                                    JobStatus::Done => "✔ Imported",
+                                    JobStatus::Failed => "Failed",
+                                };
+                                html! {
+                                    <tr class=if index == self.state.active_job { "active" } else { "" }>
+                                        <td><a href="#" onclick=onclick>{&job.title}</a></td>
+                                        <td class="right">{job.tracks.len()}</td>
+                                        <td class="right">{matched}</td>
+                                        <td>
+                                            <select onchange=onchange disabled=job.status == JobStatus::Importing>
+                                                <option value={""} selected={job.target.is_none()}>
+                                                    {format!("Create \"{}\"", job.title)}
+                                                </option>
+                                                {
+                                                    for self.state.out_playlists.iter().map(|playlist| {
+                                                        let selected = job.target.as_ref() == Some(&playlist.id);
+                                                        html! {
+                                                            <option value={&playlist.id} selected={selected}>{&playlist.name}</option>
+                                                        }
+                                                    })
+                                                }
+                                            </select>
+                                        </td>
+                                        <td>{status}</td>
+                                    </tr>
+                                }
+                            })
+                        }
+                    </tbody>
+                </table>
+                <button
+                    class="main"
+                    onclick=onclick_import_jobs
+                    disabled=self.state.importing_job.is_some() || self.state.jobs.iter().all(|job| job.status == JobStatus::Done)
+                >
+                    {"Import all playlists"}
+                </button>
+            </div>
+        }
+    }
+
+    fn read_audio_files(&mut self, files: Vec<File>) {
+        let files = files
+            .into_iter()
+            .filter(|file| is_audio_file(&file.name()))
+            .collect::<Vec<_>>();
+        if files.is_empty() {
+            self.state.error_message = Some("No audio files found".to_string());
+            return;
+        }
+
+        self.state.audio_files.clear();
+        self.audio_reader_tasks.clear();
+
+        for (index, file) in files.into_iter().enumerate() {
+            let size = file.size() as u64;
+            self.state.audio_files.push(AudioFile {
+                name: file_path(&file),
+                size,
+                head: None,
+                tail: None,
+                tags: None,
+                failed: false,
+            });
+
+            // only the tags at the beginning and the end of the file are needed
+            let chunks = if size <= HEAD_SIZE + TAIL_SIZE {
+                vec![(AudioChunk::Head, file)]
+            } else {
+                vec![
+                    (AudioChunk::Head, slice_file(&file, 0, HEAD_SIZE)),
+                    (AudioChunk::Tail, slice_file(&file, size - TAIL_SIZE, size)),
+                ]
+            };
+            for (chunk, file) in chunks {
+                let callback = self.link.callback(move |file_data: FileData| {
+                    Msg::AudioChunkLoaded(index, chunk, file_data.content)
+                });
+                match self.reader.read_file(file, callback) {
+                    Ok(reader_task) => self.audio_reader_tasks.push(reader_task),
+                    Err(_) => {
+                        self.state.audio_files.clear();
+                        self.audio_reader_tasks.clear();
+                        self.state.error_message = Some("Could not read audio files".to_string());
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    fn load_audio_files(&mut self) {
+        if !self
+            .state
+            .audio_files
+            .iter()
+            .all(|audio_file| audio_file.tags.is_some() || audio_file.failed)
+        {
+            return;
+        }
+
+        let failed_count = self
+            .state
+            .audio_files
+            .iter()
+            .filter(|audio_file| audio_file.failed)
+            .count();
+        let mut tracks = self
+            .state
+            .audio_files
+            .drain(..)
+            .filter_map(|audio_file| Some(audio_file.tags?.into_track(audio_file.name)))
+            .collect::<Vec<_>>();
+        tracks.sort_by(|a, b| a.location.cmp(&b.location));
+
+        self.state.in_files.clear();
+        self.state.selected_library_playlists.clear();
+        self.clear_jobs();
+        // the files that could be read are imported anyway
+        self.state.error_message = if failed_count > 0 {
+            Some(format!("Could not read {} audio files", failed_count))
+        } else {
+            None
+        };
+        self.load_in_playlist(Playlist::with_tracks_and_title(
+            tracks,
+            "Music folder".to_string(),
+        ));
+    }
+
+    fn load_in_playlist(&mut self, playlist: Playlist) {
+        self.state.export_file_name = playlist
+            .title
+            .as_deref()
+            .map(sanitize_file_name)
+            .filter(|file_name| !file_name.is_empty())
+            .unwrap_or_else(|| DEFAULT_EXPORT_FILE_NAME.to_string());
+        self.state.in_title = playlist.title;
+        let path_patterns = &self.state.path_patterns;
+        self.state.in_tracks = Rc::new(
+            playlist
+                .track_list
+                .tracks
+                .into_iter()
+                .map(|mut track| {
+                    infer_from_path(&mut track, path_patterns);
+                    Rc::new(track)
+                })
+                .collect(),
+        );
+
+        // tracks that link to Spotify are mapped directly, unless they were chosen by hand
+        let mut linked = HashSet::new();
+        for in_track in self.state.in_tracks.iter() {
+            if let Some(output_id) = in_track.spotify_uri() {
+                let input_id = in_track.id();
+                if !self.state.manual_mappings.contains(&input_id) {
+                    Rc::make_mut(&mut self.state.id_mapping).insert(input_id.clone(), output_id);
+                }
+                linked.insert(input_id);
+            }
+        }
+        if !linked.is_empty() {
+            self.storage
+                .store(LS_ID_MAPPING, Json(&*self.state.id_mapping));
+        }
+
+        self.state.fetch_out_tracks_queue.clear();
+        let mut queued = HashSet::new();
+        for in_track in self.state.in_tracks.iter() {
+            // tracks that were already loaded before are only searched once
+            if self.state.out_tracks.contains_key(&in_track.id())
+                || linked.contains(&in_track.id())
+                || !queued.insert(in_track.id())
+            {
+                continue;
+            }
+            if let Some(query) = search_queries(in_track).into_iter().next() {
+                self.state.fetch_out_tracks_queue.push_back((
+                    in_track.id(),
+                    query,
+                    FetchInitiator::Auto(1),
+                ));
+            }
+        }
+
+        self.state.fetch_out_tracks_remaining.clear();
+
+        for in_track in self.state.in_tracks.iter() {
+            let input_id = in_track.id();
+            if let Some(output_id) = self.state.id_mapping.get(&input_id) {
+                if !self.state.out_tracks.contains_key(output_id) {
+                    self.state
+                        .fetch_out_tracks_remaining
+                        .insert(input_id, output_id.clone());
+                }
+            }
+        }
+
+        self.fetch_next_out_track();
+    }
+
+    // the order of the candidates changes, the chosen tracks stay the same
+    fn rescore_out_tracks(&mut self) {
+        let in_tracks = self
+            .state
+            .in_tracks
+            .iter()
+            .map(|in_track| (in_track.id(), in_track.clone()))
+            .collect::<HashMap<_, _>>();
+        let settings = self.state.match_settings;
+        for (input_id, out_tracks) in Rc::make_mut(&mut self.state.out_tracks).iter_mut() {
+            let in_track = match in_tracks.get(input_id) {
+                Some(in_track) => in_track,
+                None => continue,
+            };
+            let out_tracks = Rc::make_mut(out_tracks);
+            for (similarity, out_track) in out_tracks.iter_mut() {
+                *similarity = in_track.similarity(out_track, &settings);
+            }
+            out_tracks.sort_by_key(|(similarity, _)| similarity.sort_key());
+        }
+    }
+
+    fn apply_path_patterns(&mut self) {
+        let mut changed_tracks = Vec::new();
+        let in_tracks = self
+            .state
+            .in_tracks
+            .iter()
+            .map(|in_track| {
+                let mut track = in_track.as_ref().clone();
+                infer_from_path(&mut track, &self.state.path_patterns);
+                let queries = search_queries(&track);
+                if queries != search_queries(in_track) && track.spotify_uri().is_none() {
+                    if let Some(query) = queries.into_iter().next() {
+                        changed_tracks.push((track.id(), query));
+                    }
+                }
+                Rc::new(track)
+            })
+            .collect();
+        self.state.in_tracks = Rc::new(in_tracks);
+
+        // search again for the tracks whose query changed, the ids stay the same
+        for (input_id, query) in changed_tracks {
+            Rc::make_mut(&mut self.state.out_tracks).remove(&input_id);
+            self.state
+                .fetch_out_tracks_queue
+                .push_back((input_id, query, FetchInitiator::Auto(1)));
+        }
+        self.fetch_next_out_track();
+    }
+
+    fn insert_out_track(
+        &mut self,
+        input_id: String,
+        new_out_tracks: Vec<Track>,
+        query: Option<String>,
+    ) {
+        // the input playlist might have been replaced in the meantime
+        let in_track = match self
             .state
             .in_tracks
             .iter()
             .find(|in_track| in_track.id() == *input_id)
-            .expect("received track for invalid input");
+        {
+            Some(in_track) => in_track,
+            None => return,
+        };
+
+        if let Some(query) = query {
+            for out_track in new_out_tracks.iter() {
+                self.state
+                    .out_track_queries
+                    .entry((input_id.clone(), out_track.id()))
+                    .or_insert_with(|| query.clone());
+            }
+        }
 
         // append out tracks
         let mut new_out_tracks = new_out_tracks
             .into_iter()
-            .map(|out_track| (in_track.similarity(&out_track), out_track))
+            .map(|out_track| {
+                (
+                    in_track.similarity(&out_track, &self.state.match_settings),
+                    out_track,
+                )
+            })
             .collect::<Vec<_>>();
         let out_tracks = Rc::make_mut(
             Rc::make_mut(&mut self.state.out_tracks)
@@ -423,7 +1637,7 @@ impl Import {
                 .or_default(),
         );
         out_tracks.append(&mut new_out_tracks);
-        out_tracks.sort_by_key(|(similarity, _)| -(similarity * 1_000.0) as isize);
+        out_tracks.sort_by_key(|(similarity, _)| similarity.sort_key());
 
         // set default mapping
         if !self.state.id_mapping.contains_key(&input_id) {
@@ -443,6 +1657,127 @@ impl Import {
         }
     }
 
+    fn summary(&self) -> ImportSummary {
+        // name the Spotify playlist the tracks were imported into, if there is a single one
+        let playlist_name = self
+            .state
+            .selected_out_playlist
+            .as_ref()
+            .filter(|_| self.state.jobs.is_empty())
+            .and_then(|playlist_id| {
+                self.state
+                    .out_playlists
+                    .iter()
+                    .find(|playlist| playlist.id == *playlist_id)
+            })
+            .map(|playlist| playlist.name.clone())
+            .or_else(|| self.state.in_title.clone())
+            .unwrap_or_default();
+        let date = String::from(js_sys::Date::new_0().to_iso_string());
+        let date = format!("{} UTC", date.get(..16).unwrap_or(&date).replace('T', " "));
+        let skipped = self
+            .state
+            .in_files
+            .iter()
+            .map(|in_file| in_file.skipped_tracks.len())
+            .sum();
+        ImportSummary::new(
+            playlist_name,
+            date,
+            &self.state.in_tracks,
+            &self.state.out_tracks,
+            &self.state.id_mapping,
+            skipped,
+        )
+    }
+
+    fn report_row(&self, in_track: &Track) -> MatchReportRow {
+        let input_id = in_track.id();
+        let output_id = self.state.id_mapping.get(&input_id);
+        let out_track = output_id.and_then(|output_id| {
+            self.state
+                .out_tracks
+                .get(&input_id)?
+                .iter()
+                .map(|(_, out_track)| out_track)
+                .find(|out_track| out_track.id() == *output_id)
+        });
+        let selection = if self.state.manual_mappings.contains(&input_id) {
+            Selection::Manual
+        } else {
+            Selection::Automatic
+        };
+        let query = output_id.and_then(|output_id| {
+            self.state
+                .out_track_queries
+                .get(&(input_id.clone(), output_id.clone()))
+                .cloned()
+        });
+        MatchReportRow::new(
+            in_track,
+            output_id.map(String::as_str),
+            out_track,
+            Some(selection),
+            query,
+            &self.state.match_settings,
+        )
+    }
+
+    fn fetch_spotify_export_page(&mut self, url: String) {
+        let request = Request::get(url)
+            .header(
+                "Authorization",
+                format!("Bearer {}", self.props.spotify_user.access_token),
+            )
+            .body(Nothing)
+            .expect("failed to build request");
+
+        if let Ok(task) = FetchService::fetch(
+            request,
+            self.link.callback(
+                move |response: Response<
+                    Json<Result<SpotifyPagination<SpotifyPlaylistItem>, Error>>,
+                >| {
+                    if let (meta, Json(Ok(items))) = response.into_parts() {
+                        if meta.status.is_success() {
+                            let tracks = items
+                                .items
+                                .into_iter()
+                                .filter_map(|item| {
+                                    serde_json::from_value::<SpotifyTrack>(item.track?).ok()
+                                })
+                                .map(Into::into)
+                                .collect();
+                            return Msg::SpotifyExportPageLoaded(tracks, items.next);
+                        }
+                    }
+                    Msg::SetError("Request failed: get playlist tracks".to_string())
+                },
+            ),
+        ) {
+            self.fetch_tasks.push(FetchTask::from(task));
+        }
+    }
+
+    fn download_spotify_export(&mut self) {
+        let tracks = self.state.spotify_export_tracks.take().unwrap_or_default();
+        let name = self
+            .state
+            .out_playlists
+            .iter()
+            .chain(self.state.followed_playlists.iter())
+            .find(|playlist| Some(&playlist.id) == self.state.spotify_export_playlist.as_ref())
+            .map_or_else(
+                || DEFAULT_EXPORT_FILE_NAME.to_string(),
+                |playlist| playlist.name.clone(),
+            );
+        let format = self.state.spotify_export_format;
+        let content = Playlist::with_tracks_and_title(tracks, name.clone()).export(format);
+        unsafe {
+            download_file(&export_file_name(&name, format.extension()), &content);
+        }
+    }
+
     fn get_playlists(&mut self) {
         let request = Request::get(format!(
             "https://api.spotify.com/v1/users/{}/playlists?limit=50",
@@ -455,24 +1790,13 @@ impl Import {
         .body(Nothing)
         .expect("failed to build request");
 
-        let spotify_user_id = self.props.spotify_user.user_id.clone();
-
         if let Ok(task) = FetchService::fetch(
             request,
             self.link.callback(
-                move |response: Response<
-                    Json<Result<SpotifyPagination<SpotifyPlaylist>, Error>>,
-                >| {
+                |response: Response<Json<Result<SpotifyPagination<SpotifyPlaylist>, Error>>>| {
                     if let (meta, Json(Ok(playlists))) = response.into_parts() {
                         if meta.status.is_success() {
-                            let playlists = playlists
-                                .items
-                                .into_iter()
-                                .filter(|playlist| {
-                                    playlist.owner.id == spotify_user_id || playlist.collaborative
-                                })
-                                .collect();
-                            return Msg::OutPlaylistsLoaded(playlists);
+                            return Msg::OutPlaylistsLoaded(playlists.items);
                         }
                     }
                     Msg::SetError("Request failed: get playlists".to_string())
@@ -495,14 +1819,9 @@ impl Import {
             }
         }
 
-        let batch_count =
-            (self.state.fetch_out_tracks_remaining.len() as f64 / 50.0).ceil() as usize;
-
-        if self.fetch_tasks.len() == 0
-            && self.state.fetch_out_tracks_remaining_batch_index < batch_count
-        {
+        // every batch is removed from the remaining tracks once it is received
+        if self.fetch_tasks.len() == 0 && !self.state.fetch_out_tracks_remaining.is_empty() {
             self.fetch_remaining_out_tracks();
-            self.state.fetch_out_tracks_remaining_batch_index += 1;
         }
     }
 
@@ -535,7 +1854,12 @@ impl Import {
                                 .into_iter()
                                 .map(Into::into)
                                 .collect::<Vec<Track>>();
-                            return Msg::OutTracksFound(input_id.clone(), tracks, fetch_initiator);
+                            return Msg::OutTracksFound(
+                                input_id.clone(),
+                                query.clone(),
+                                tracks,
+                                fetch_initiator,
+                            );
                         }
                     }
                     Msg::SetError("Request failed: search track".to_string())
@@ -547,14 +1871,17 @@ impl Import {
     }
 
     fn fetch_remaining_out_tracks(&mut self) {
-        let spotify_ids = self
+        let batch = self
             .state
             .fetch_out_tracks_remaining
-            .values()
-            .skip(self.state.fetch_out_tracks_remaining_batch_index * 50)
+            .iter()
             .take(50)
-            .map(AsRef::as_ref)
-            .map(parse_spotify_id)
+            .map(|(input_id, output_id)| (input_id.clone(), output_id.clone()))
+            .collect::<Vec<_>>();
+
+        let spotify_ids = batch
+            .iter()
+            .map(|(_, output_id)| parse_spotify_id(output_id))
             .collect::<Vec<_>>()
             .join(",");
 
@@ -569,14 +1896,18 @@ impl Import {
         .body(Nothing)
         .expect("failed to build request");
 
-        let id_lookup = self
-            .state
-            .fetch_out_tracks_remaining
-            .iter()
-            .skip(self.state.fetch_out_tracks_remaining_batch_index * 50)
-            .take(50)
-            .map(|(input_id, output_id)| (output_id.clone(), input_id.clone()))
-            .collect::<HashMap<_, _>>();
+        // several input tracks can link to the same Spotify track
+        let mut id_lookup = HashMap::<String, Vec<String>>::new();
+        for (input_id, output_id) in batch.iter() {
+            id_lookup
+                .entry(output_id.clone())
+                .or_default()
+                .push(input_id.clone());
+        }
+        let input_ids = batch
+            .into_iter()
+            .map(|(input_id, _)| input_id)
+            .collect::<Vec<_>>();
 
         if let Ok(task) = FetchService::fetch(
             request,
@@ -584,20 +1915,22 @@ impl Import {
                 move |response: Response<Json<Result<SpotifyTracks, Error>>>| {
                     if let (meta, Json(Ok(tracks))) = response.into_parts() {
                         if meta.status.is_success() {
+                            // unknown ids are returned as null, relinked ones under another id
                             let tracks = tracks
                                 .tracks
                                 .into_iter()
-                                .map(|track| {
-                                    let out_track = Track::from(track);
-                                    let output_id = out_track.id();
-                                    let input_id = id_lookup
-                                        .get(&output_id)
-                                        .expect("received unexpected track")
-                                        .to_string();
-                                    (input_id, out_track)
+                                .flatten()
+                                .map(Track::from)
+                                .flat_map(|out_track| {
+                                    id_lookup
+                                        .get(&out_track.id())
+                                        .into_iter()
+                                        .flatten()
+                                        .map(|input_id| (input_id.clone(), out_track.clone()))
+                                        .collect::<Vec<_>>()
                                 })
                                 .collect::<Vec<_>>();
-                            return Msg::RemainingOutTracksFound(tracks);
+                            return Msg::RemainingOutTracksFound(input_ids.clone(), tracks);
                         }
                     }
                     Msg::SetError("Request failed: get tracks".to_string())
@@ -608,25 +1941,82 @@ impl Import {
         }
     }
 
-    fn add_next_to_playlist(&mut self, playlist_id: &str) {
-        let batch_count = (self.state.in_tracks.len() as f64 / 50.0).ceil() as usize;
+    fn import_next_job(&mut self) {
+        let index = match self
+            .state
+            .jobs
+            .iter()
+            .position(|job| job.status == JobStatus::Ready)
+        {
+            Some(index) => index,
+            None => {
+                self.state.importing_job = None;
+                self.state.import_matched_done = true;
+                return;
+            }
+        };
+
+        let job = &mut self.state.jobs[index];
+        job.status = JobStatus::Importing;
+        self.state.importing_job = Some(index);
+        self.state.import_matched_batch_index = 0;
+        match job.target.clone() {
+            Some(playlist_id) => {
+                self.state.import_playlist_id = Some(playlist_id);
+                self.import_next_batch();
+            }
+            None => {
+                let title = job.title.clone();
+                self.create_playlist(title, move |playlist| {
+                    Msg::JobPlaylistCreated(index, playlist)
+                });
+            }
+        }
+    }
+
+    // adds the next 50 songs, or continues with the next job once all songs have been added
+    fn import_next_batch(&mut self) {
+        let playlist_id = match self.state.import_playlist_id.clone() {
+            Some(playlist_id) => playlist_id,
+            None => return,
+        };
+        let range = match self.state.importing_job {
+            Some(index) => match self.state.jobs.get(index) {
+                Some(job) => job.tracks.clone(),
+                None => return,
+            },
+            None => 0..self.state.in_tracks.len(),
+        };
+        let in_tracks = self.state.in_tracks.clone();
+        let in_tracks = &in_tracks[range];
 
-        if self.state.import_matched_batch_index < batch_count {
-            self.add_to_playlist(playlist_id);
+        while self.state.import_matched_batch_index * 50 < in_tracks.len() {
+            let uris = in_tracks
+                .iter()
+                .skip(self.state.import_matched_batch_index * 50)
+                .take(50)
+                .filter_map(|in_track| self.state.id_mapping.get(&in_track.id()))
+                .cloned()
+                .collect::<Vec<_>>();
             self.state.import_matched_batch_index += 1;
+            if !uris.is_empty() {
+                self.add_to_playlist(&playlist_id, uris);
+                return;
+            }
+        }
+
+        match self.state.importing_job {
+            Some(index) => {
+                if let Some(job) = self.state.jobs.get_mut(index) {
+                    job.status = JobStatus::Done;
+                }
+                self.import_next_job();
+            }
+            None => self.state.import_matched_done = true,
         }
     }
 
-    fn add_to_playlist(&mut self, playlist_id: &str) {
-        let uris = self
-            .state
-            .in_tracks
-            .iter()
-            .skip(self.state.import_matched_batch_index * 50)
-            .take(50)
-            .filter_map(|in_track| self.state.id_mapping.get(&in_track.id()))
-            .cloned()
-            .collect::<Vec<_>>();
+    fn add_to_playlist(&mut self, playlist_id: &str, uris: Vec<String>) {
         let body_json = serde_json::json!({ "uris": uris });
 
         let request = Request::post(format!(
@@ -654,7 +2044,11 @@ impl Import {
         }
     }
 
-    fn create_playlist(&mut self, name: String) {
+    fn create_playlist(
+        &mut self,
+        name: String,
+        on_created: impl Fn(SpotifyPlaylist) -> Msg + 'static,
+    ) {
         let playlist = SpotifyCreatePlaylist {
             name,
             public: false,
@@ -678,7 +2072,7 @@ impl Import {
                 move |response: Response<Json<Result<SpotifyPlaylist, Error>>>| {
                     if let (meta, Json(Ok(playlist))) = response.into_parts() {
                         if meta.status.is_success() {
-                            return Msg::OutPlaylistCreated(playlist);
+                            return on_created(playlist);
                         }
                     }
                     Msg::SetError("Request failed: create playlist".to_string())
@@ -690,6 +2084,63 @@ impl Import {
     }
 }
 
+fn parse_in_file(file_data: &FileData, format: PlaylistFormat) -> (InFileContent, Vec<ParseError>) {
+    match parse_input(format, &file_data.content) {
+        Ok((ParsedInput::Playlist(playlist), skipped_tracks)) => {
+            (InFileContent::Playlists(vec![*playlist]), skipped_tracks)
+        }
+        Ok((ParsedInput::Library(library), skipped_tracks)) => {
+            (InFileContent::Library(Rc::new(library)), skipped_tracks)
+        }
+        Ok((ParsedInput::Table(content), skipped_tracks)) => {
+            (InFileContent::Table(Rc::new(content)), skipped_tracks)
+        }
+        Err(error) => (InFileContent::Error(error), Vec::new()),
+    }
+}
+
+// files from a selected folder know their path relative to it
+fn file_path(file: &File) -> String {
+    js_sys::Reflect::get(file, &"webkitRelativePath".into())
+        .ok()
+        .and_then(|path| path.as_string())
+        .filter(|path| !path.is_empty())
+        .unwrap_or_else(|| file.name())
+}
+
+// replaces the characters that are not allowed in file names on common systems
+fn sanitize_file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect::<String>()
+        .trim_matches(|c: char| c.is_whitespace() || c == '.')
+        .to_owned()
+}
+
+fn export_file_name(name: &str, extension: &str) -> String {
+    let name = sanitize_file_name(name);
+    let extension = format!(".{}", extension);
+    // the extension is added for the selected format, even if the user already typed it
+    let name = match name.strip_suffix(extension.as_str()) {
+        Some(stem) if !stem.is_empty() => stem,
+        _ if name.is_empty() => DEFAULT_EXPORT_FILE_NAME,
+        _ => name.as_str(),
+    };
+    format!("{}{}", name, extension)
+}
+
+fn slice_file(file: &File, start: u64, end: u64) -> File {
+    let blob = file
+        .slice_with_f64_and_f64(start as f64, end as f64)
+        .expect("failed to slice file");
+    File::new_with_blob_sequence(&js_sys::Array::of1(&blob), &file.name())
+        .expect("failed to create file")
+}
+
 fn parse_spotify_id(uri: &str) -> &str {
     uri.split(':').last().expect("invalid spotify uri")
 }
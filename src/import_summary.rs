@@ -0,0 +1,317 @@
+use crate::playlist_types::*;
+use strum_macros::{Display, EnumIter, EnumString};
+use xml::escape::escape_str_pcdata;
+
+use std::collections::HashMap;
+use std::rc::Rc;
+
+// matches below this similarity are listed for review
+const LOW_CONFIDENCE: f64 = 0.75;
+const HISTOGRAM_BUCKETS: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Display, EnumIter, EnumString)]
+pub enum SummaryFormat {
+    #[strum(serialize = "HTML")]
+    Html,
+    #[strum(serialize = "Markdown")]
+    Markdown,
+}
+
+impl SummaryFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            SummaryFormat::Html => "html",
+            SummaryFormat::Markdown => "md",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchedTrack {
+    pub in_track: Rc<Track>,
+    // the Spotify track is unknown while a stored mapping hasn't been loaded yet
+    pub out_track: Option<(f64, Track)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportSummary {
+    pub playlist_name: String,
+    pub date: String,
+    pub matched: Vec<MatchedTrack>,
+    pub unmatched: Vec<Rc<Track>>,
+    pub skipped: usize,
+}
+
+impl ImportSummary {
+    pub fn new(
+        playlist_name: String,
+        date: String,
+        in_tracks: &[Rc<Track>],
+        out_tracks: &HashMap<String, Rc<Vec<(Similarity, Track)>>>,
+        id_mapping: &HashMap<String, String>,
+        skipped: usize,
+    ) -> ImportSummary {
+        let mut matched = Vec::new();
+        let mut unmatched = Vec::new();
+        for in_track in in_tracks {
+            let input_id = in_track.id();
+            match id_mapping.get(&input_id) {
+                Some(output_id) => matched.push(MatchedTrack {
+                    in_track: in_track.clone(),
+                    out_track: out_tracks.get(&input_id).and_then(|out_tracks| {
+                        out_tracks
+                            .iter()
+                            .find(|(_, out_track)| out_track.id() == *output_id)
+                            .map(|(similarity, out_track)| (similarity.score, out_track.clone()))
+                    }),
+                }),
+                None => unmatched.push(in_track.clone()),
+            }
+        }
+        ImportSummary {
+            playlist_name,
+            date,
+            matched,
+            unmatched,
+            skipped,
+        }
+    }
+
+    pub fn export(&self, format: SummaryFormat) -> String {
+        match format {
+            SummaryFormat::Html => self.to_html(),
+            SummaryFormat::Markdown => self.to_markdown(),
+        }
+    }
+
+    // the number of matches per similarity range of a tenth
+    pub fn histogram(&self) -> [usize; HISTOGRAM_BUCKETS] {
+        let mut histogram = [0; HISTOGRAM_BUCKETS];
+        for (similarity, _) in self
+            .matched
+            .iter()
+            .filter_map(|track| track.out_track.as_ref())
+        {
+            if similarity.is_finite() {
+                let bucket = (similarity.max(0.0) * HISTOGRAM_BUCKETS as f64) as usize;
+                histogram[bucket.min(HISTOGRAM_BUCKETS - 1)] += 1;
+            }
+        }
+        histogram
+    }
+
+    pub fn low_confidence(&self) -> Vec<(&Track, f64, &Track)> {
+        self.matched
+            .iter()
+            .filter_map(|track| {
+                let (similarity, out_track) = track.out_track.as_ref()?;
+                if *similarity < LOW_CONFIDENCE || !similarity.is_finite() {
+                    Some((track.in_track.as_ref(), *similarity, out_track))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    fn histogram_rows(&self) -> Vec<(String, usize)> {
+        self.histogram()
+            .iter()
+            .enumerate()
+            .map(|(bucket, count)| {
+                let start = bucket as f64 / HISTOGRAM_BUCKETS as f64;
+                let end = (bucket + 1) as f64 / HISTOGRAM_BUCKETS as f64;
+                (format!("{:.1} – {:.1}", start, end), *count)
+            })
+            .collect()
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let cell = |value: &str| value.replace('\\', "\\\\").replace('|', "\\|");
+        let mut lines = vec![
+            format!("# Import summary: {}", cell(&self.playlist_name)),
+            String::new(),
+            format!("- Date: {}", self.date),
+            format!("- Matched: {}", self.matched.len()),
+            format!("- Unmatched: {}", self.unmatched.len()),
+            format!("- Skipped: {}", self.skipped),
+            String::new(),
+            "## Similarity".to_owned(),
+            String::new(),
+            "| Similarity | Tracks | |".to_owned(),
+            "| --- | ---: | --- |".to_owned(),
+        ];
+        for (range, count) in self.histogram_rows() {
+            lines.push(format!("| {} | {} | {} |", range, count, "█".repeat(count)));
+        }
+
+        lines.extend(vec![
+            String::new(),
+            "## Low confidence matches".to_owned(),
+            String::new(),
+            "| Title | Artist | Spotify title | Spotify artist | Similarity |".to_owned(),
+            "| --- | --- | --- | --- | ---: |".to_owned(),
+        ]);
+        for (in_track, similarity, out_track) in self.low_confidence() {
+            lines.push(format!(
+                "| {} | {} | {} | {} | {:.2} |",
+                cell(in_track.title.as_deref().unwrap_or_default()),
+                cell(in_track.artist.as_deref().unwrap_or_default()),
+                cell(out_track.title.as_deref().unwrap_or_default()),
+                cell(out_track.artist.as_deref().unwrap_or_default()),
+                similarity
+            ));
+        }
+
+        lines.extend(vec![
+            String::new(),
+            "## Unmatched tracks".to_owned(),
+            String::new(),
+            "| Title | Artist | Album |".to_owned(),
+            "| --- | --- | --- |".to_owned(),
+        ]);
+        for in_track in &self.unmatched {
+            lines.push(format!(
+                "| {} | {} | {} |",
+                cell(in_track.title.as_deref().unwrap_or_default()),
+                cell(in_track.artist.as_deref().unwrap_or_default()),
+                cell(in_track.album.as_deref().unwrap_or_default())
+            ));
+        }
+
+        lines.push(String::new());
+        lines.join("\n")
+    }
+
+    pub fn to_html(&self) -> String {
+        let text = |value: Option<&str>| escape_str_pcdata(value.unwrap_or_default()).into_owned();
+        let max_count = self
+            .histogram()
+            .iter()
+            .copied()
+            .max()
+            .unwrap_or_default()
+            .max(1);
+
+        let mut html = vec![
+            "<!DOCTYPE html>".to_owned(),
+            "<html>".to_owned(),
+            "<head>".to_owned(),
+            "<meta charset=\"utf-8\">".to_owned(),
+            format!("<title>Import summary: {}</title>", text(Some(&self.playlist_name))),
+            "<style>body { font-family: sans-serif; } table { border-collapse: collapse; margin-bottom: 1em; } th, td { border: 1px solid #ccc; padding: 2px 8px; text-align: left; } .right { text-align: right; } .bar { background: #1db954; height: 1em; }</style>".to_owned(),
+            "</head>".to_owned(),
+            "<body>".to_owned(),
+            format!("<h1>Import summary: {}</h1>", text(Some(&self.playlist_name))),
+            "<ul>".to_owned(),
+            format!("<li>Date: {}</li>", text(Some(&self.date))),
+            format!("<li>Matched: {}</li>", self.matched.len()),
+            format!("<li>Unmatched: {}</li>", self.unmatched.len()),
+            format!("<li>Skipped: {}</li>", self.skipped),
+            "</ul>".to_owned(),
+            "<h2>Similarity</h2>".to_owned(),
+            "<table>".to_owned(),
+            "<tr><th>Similarity</th><th class=\"right\">Tracks</th><th></th></tr>".to_owned(),
+        ];
+        for (range, count) in self.histogram_rows() {
+            html.push(format!(
+                "<tr><td>{}</td><td class=\"right\">{}</td><td><div class=\"bar\" style=\"width: {}px\"></div></td></tr>",
+                range,
+                count,
+                count * 200 / max_count
+            ));
+        }
+        html.push("</table>".to_owned());
+
+        html.push("<h2>Low confidence matches</h2>".to_owned());
+        html.push("<table>".to_owned());
+        html.push("<tr><th>Title</th><th>Artist</th><th>Spotify title</th><th>Spotify artist</th><th class=\"right\">Similarity</th></tr>".to_owned());
+        for (in_track, similarity, out_track) in self.low_confidence() {
+            html.push(format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td class=\"right\">{:.2}</td></tr>",
+                text(in_track.title.as_deref()),
+                text(in_track.artist.as_deref()),
+                text(out_track.title.as_deref()),
+                text(out_track.artist.as_deref()),
+                similarity
+            ));
+        }
+        html.push("</table>".to_owned());
+
+        html.push("<h2>Unmatched tracks</h2>".to_owned());
+        html.push("<table>".to_owned());
+        html.push("<tr><th>Title</th><th>Artist</th><th>Album</th></tr>".to_owned());
+        for in_track in &self.unmatched {
+            html.push(format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+                text(in_track.title.as_deref()),
+                text(in_track.artist.as_deref()),
+                text(in_track.album.as_deref())
+            ));
+        }
+        html.push("</table>".to_owned());
+
+        html.push("</body>".to_owned());
+        html.push("</html>".to_owned());
+        html.push(String::new());
+        html.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    fn track(title: &str, duration: i32) -> Track {
+        Track {
+            title: Some(title.to_string()),
+            artist: Some("Artist".to_string()),
+            duration: Some(duration),
+            ..Default::default()
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn summary() {
+        let in_tracks = vec![
+            Rc::new(track("Good", 200_000)),
+            Rc::new(track("Poor <live>", 200_000)),
+            Rc::new(track("Missing", 200_000)),
+        ];
+        let scored = |score: f64, track: Track| {
+            let similarity = Similarity {
+                score,
+                ..Default::default()
+            };
+            (similarity, track)
+        };
+        let good = scored(0.95, track("Good", 200_000));
+        let poor = scored(0.5, track("Other", 100_000));
+
+        let mut out_tracks = HashMap::new();
+        out_tracks.insert(in_tracks[0].id(), Rc::new(vec![good.clone()]));
+        out_tracks.insert(in_tracks[1].id(), Rc::new(vec![poor.clone()]));
+        let mut id_mapping = HashMap::new();
+        id_mapping.insert(in_tracks[0].id(), good.1.id());
+        id_mapping.insert(in_tracks[1].id(), poor.1.id());
+
+        let summary = ImportSummary::new(
+            "Mix".to_string(),
+            "2020-01-01".to_string(),
+            &in_tracks,
+            &out_tracks,
+            &id_mapping,
+            1,
+        );
+        assert_eq!(2, summary.matched.len());
+        assert_eq!(vec![in_tracks[2].clone()], summary.unmatched);
+        assert_eq!([0, 0, 0, 0, 0, 1, 0, 0, 0, 1], summary.histogram());
+        assert_eq!(1, summary.low_confidence().len());
+
+        let markdown = summary.to_markdown();
+        assert!(markdown.contains("- Skipped: 1\n"));
+        assert!(markdown.contains("| Poor <live> | Artist | Other | Artist | 0.50 |"));
+        assert!(summary.to_html().contains("<td>Poor &lt;live&gt;</td>"));
+    }
+}
@@ -0,0 +1,339 @@
+use crate::m3u::non_empty;
+use crate::playlist_types::*;
+
+use xml::reader::{EventReader, XmlEvent};
+
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Library {
+    pub tracks: HashMap<i64, Track>,
+    pub playlists: Vec<LibraryPlaylist>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LibraryPlaylist {
+    pub name: String,
+    pub track_ids: Vec<i64>,
+}
+
+impl Library {
+    pub fn to_playlist(&self, playlist_indices: &[usize]) -> Playlist {
+        let tracks = playlist_indices
+            .iter()
+            .filter_map(|index| self.playlists.get(*index))
+            .flat_map(|playlist| playlist.track_ids.iter())
+            .filter_map(|track_id| self.tracks.get(track_id))
+            .cloned()
+            .collect();
+        let title = playlist_indices
+            .iter()
+            .filter_map(|index| self.playlists.get(*index))
+            .map(|playlist| playlist.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        Playlist::with_tracks_and_title(tracks, title)
+    }
+}
+
+#[derive(Debug)]
+enum PlistValue {
+    Dict(HashMap<String, PlistValue>),
+    Array(Vec<PlistValue>),
+    String(String),
+    Integer(i64),
+    Real(f64),
+    Bool(bool),
+}
+
+impl PlistValue {
+    fn get(&self, key: &str) -> Option<&PlistValue> {
+        match self {
+            PlistValue::Dict(dict) => dict.get(key),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            PlistValue::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    fn as_i64(&self) -> Option<i64> {
+        match self {
+            PlistValue::Integer(value) => Some(*value),
+            PlistValue::Real(value) => Some(*value as i64),
+            _ => None,
+        }
+    }
+
+    fn as_bool(&self) -> Option<bool> {
+        match self {
+            PlistValue::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    fn as_array(&self) -> &[PlistValue] {
+        match self {
+            PlistValue::Array(values) => values,
+            _ => &[],
+        }
+    }
+}
+
+enum Container {
+    Dict(HashMap<String, PlistValue>, Option<String>),
+    Array(Vec<PlistValue>),
+}
+
+pub fn parse_itunes_library(content: &[u8]) -> Result<Library, xml::reader::Error> {
+    let root = match parse_plist(content)? {
+        Some(root) => root,
+        None => return Ok(Library::default()),
+    };
+
+    let tracks = match root.get("Tracks") {
+        Some(PlistValue::Dict(tracks)) => tracks
+            .values()
+            .filter_map(|track| Some((track.get("Track ID")?.as_i64()?, convert_track(track))))
+            .collect(),
+        _ => HashMap::new(),
+    };
+
+    let playlists = root
+        .get("Playlists")
+        .map(PlistValue::as_array)
+        .unwrap_or_default()
+        .iter()
+        .filter(|playlist| {
+            // folders only aggregate the tracks of their child playlists, the master playlist
+            // contains the whole library and distinguished ones are Music, Podcasts, Purchased, ...
+            let flag = |key| {
+                playlist
+                    .get(key)
+                    .and_then(PlistValue::as_bool)
+                    .unwrap_or_default()
+            };
+            !flag("Folder") && !flag("Master") && playlist.get("Distinguished Kind").is_none()
+        })
+        .map(|playlist| LibraryPlaylist {
+            name: playlist
+                .get("Name")
+                .and_then(PlistValue::as_str)
+                .unwrap_or_default()
+                .to_owned(),
+            track_ids: playlist
+                .get("Playlist Items")
+                .map(PlistValue::as_array)
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|item| item.get("Track ID")?.as_i64())
+                .collect(),
+        })
+        .filter(|playlist| !playlist.track_ids.is_empty())
+        .collect();
+
+    Ok(Library { tracks, playlists })
+}
+
+fn convert_track(track: &PlistValue) -> Track {
+    let string = |key| {
+        track
+            .get(key)
+            .and_then(PlistValue::as_str)
+            .and_then(non_empty)
+    };
+    let integer = |key| track.get(key).and_then(PlistValue::as_i64);
+
+    Track {
+        location: string("Location"),
+        title: string("Name"),
+        artist: string("Artist").or_else(|| string("Album Artist")),
+        album: string("Album"),
+        track_number: integer("Track Number").map(|track_number| track_number as i32),
+        duration: integer("Total Time").map(|duration| duration as i32),
+        year: integer("Year")
+            .filter(|year| *year > 0)
+            .map(|year| year as i32),
+        ..Default::default()
+    }
+}
+
+fn parse_plist(content: &[u8]) -> Result<Option<PlistValue>, xml::reader::Error> {
+    let mut containers = Vec::<Container>::new();
+    let mut text = String::new();
+
+    for event in EventReader::new(content) {
+        let value = match event? {
+            XmlEvent::StartElement { name, .. } => {
+                match name.local_name.as_str() {
+                    "dict" => containers.push(Container::Dict(HashMap::new(), None)),
+                    "array" => containers.push(Container::Array(Vec::new())),
+                    _ => text.clear(),
+                }
+                continue;
+            }
+            XmlEvent::Characters(characters) | XmlEvent::CData(characters) => {
+                text.push_str(&characters);
+                continue;
+            }
+            XmlEvent::EndElement { name } => match name.local_name.as_str() {
+                "dict" | "array" => match containers.pop() {
+                    Some(Container::Dict(dict, _)) => PlistValue::Dict(dict),
+                    Some(Container::Array(array)) => PlistValue::Array(array),
+                    None => continue,
+                },
+                "key" => {
+                    if let Some(Container::Dict(_, key)) = containers.last_mut() {
+                        *key = Some(std::mem::take(&mut text));
+                    }
+                    continue;
+                }
+                "integer" => PlistValue::Integer(text.trim().parse().unwrap_or_default()),
+                "real" => PlistValue::Real(text.trim().parse().unwrap_or_default()),
+                "true" => PlistValue::Bool(true),
+                "false" => PlistValue::Bool(false),
+                "plist" => continue,
+                _ => PlistValue::String(std::mem::take(&mut text)),
+            },
+            _ => continue,
+        };
+
+        match containers.last_mut() {
+            Some(Container::Dict(dict, key)) => {
+                if let Some(key) = key.take() {
+                    dict.insert(key, value);
+                }
+            }
+            Some(Container::Array(array)) => array.push(value),
+            None => return Ok(Some(value)),
+        }
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    const LIBRARY: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple Computer//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+	<key>Major Version</key><integer>1</integer>
+	<key>Tracks</key>
+	<dict>
+		<key>101</key>
+		<dict>
+			<key>Track ID</key><integer>101</integer>
+			<key>Name</key><string>Title &amp; More</string>
+			<key>Artist</key><string>Artist</string>
+			<key>Album</key><string>Album</string>
+			<key>Track Number</key><integer>3</integer>
+			<key>Total Time</key><integer>215000</integer>
+			<key>Year</key><integer>1976</integer>
+			<key>Location</key><string>file:///Music/01.m4a</string>
+		</dict>
+		<key>102</key>
+		<dict>
+			<key>Track ID</key><integer>102</integer>
+			<key>Name</key><string>Other</string>
+			<key>Album Artist</key><string>Album Artist</string>
+			<key>Podcast</key><true/>
+		</dict>
+	</dict>
+	<key>Playlists</key>
+	<array>
+		<dict>
+			<key>Name</key><string>Library</string>
+			<key>Master</key><true/>
+			<key>Playlist Items</key>
+			<array>
+				<dict><key>Track ID</key><integer>101</integer></dict>
+				<dict><key>Track ID</key><integer>102</integer></dict>
+			</array>
+		</dict>
+		<dict>
+			<key>Name</key><string>Podcasts</string>
+			<key>Distinguished Kind</key><integer>10</integer>
+			<key>Playlist Items</key>
+			<array>
+				<dict><key>Track ID</key><integer>102</integer></dict>
+			</array>
+		</dict>
+		<dict>
+			<key>Name</key><string>Folder</string>
+			<key>Folder</key><true/>
+			<key>Playlist Items</key>
+			<array>
+				<dict><key>Track ID</key><integer>101</integer></dict>
+			</array>
+		</dict>
+		<dict>
+			<key>Name</key><string>Mix</string>
+			<key>Playlist Items</key>
+			<array>
+				<dict><key>Track ID</key><integer>102</integer></dict>
+				<dict><key>Track ID</key><integer>101</integer></dict>
+				<dict><key>Track ID</key><integer>999</integer></dict>
+			</array>
+		</dict>
+		<dict>
+			<key>Name</key><string>Empty</string>
+		</dict>
+	</array>
+</dict>
+</plist>"#;
+
+    #[wasm_bindgen_test]
+    fn library() {
+        let library = parse_itunes_library(LIBRARY.as_bytes()).unwrap();
+
+        assert_eq!(
+            vec![LibraryPlaylist {
+                name: "Mix".to_string(),
+                track_ids: vec![102, 101, 999],
+            }],
+            library.playlists
+        );
+        assert_eq!(
+            Some(&Track {
+                location: Some("file:///Music/01.m4a".to_string()),
+                title: Some("Title & More".to_string()),
+                artist: Some("Artist".to_string()),
+                album: Some("Album".to_string()),
+                track_number: Some(3),
+                duration: Some(215_000),
+                year: Some(1976),
+                ..Default::default()
+            }),
+            library.tracks.get(&101)
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn to_playlist() {
+        let library = parse_itunes_library(LIBRARY.as_bytes()).unwrap();
+        let playlist = library.to_playlist(&[0, 7]);
+
+        assert_eq!(Some("Mix".to_string()), playlist.title);
+        assert_eq!(
+            vec![Some("Other"), Some("Title & More")],
+            playlist
+                .track_list
+                .tracks
+                .iter()
+                .map(|track| track.title.as_deref())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            Some("Album Artist".to_string()),
+            playlist.track_list.tracks[0].artist
+        );
+    }
+}
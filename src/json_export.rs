@@ -0,0 +1,43 @@
+use crate::playlist_types::*;
+
+#[derive(Debug, Serialize)]
+struct JsonPlaylist<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<&'a str>,
+    tracks: Vec<JsonTrack<'a>>,
+}
+
+// a flat list of tracks for scripts and spreadsheets, unlike JSPF it doesn't follow a spec
+#[derive(Debug, Serialize)]
+struct JsonTrack<'a> {
+    title: Option<&'a str>,
+    artist: Option<&'a str>,
+    album: Option<&'a str>,
+    duration_ms: Option<i32>,
+    track_number: Option<i32>,
+    location: Option<&'a str>,
+    identifier: Option<&'a str>,
+    isrc: Option<&'a str>,
+}
+
+pub fn write_json(playlist: &Playlist) -> String {
+    let document = JsonPlaylist {
+        title: playlist.title.as_deref(),
+        tracks: playlist
+            .track_list
+            .tracks
+            .iter()
+            .map(|track| JsonTrack {
+                title: track.title.as_deref(),
+                artist: track.artist.as_deref(),
+                album: track.album.as_deref(),
+                duration_ms: track.duration,
+                track_number: track.track_number,
+                location: track.location.as_deref(),
+                identifier: track.identifier.as_deref(),
+                isrc: track.isrc.as_deref(),
+            })
+            .collect(),
+    };
+    serde_json::to_string_pretty(&document).expect("serialize playlist")
+}
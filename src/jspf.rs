@@ -0,0 +1,332 @@
+use crate::parse_error::ParseError;
+use crate::playlist_types::*;
+
+use std::collections::BTreeMap;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct JspfDocument<T> {
+    playlist: JspfPlaylist<T>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct JspfPlaylist<T> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    creator: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    annotation: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    info: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    location: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    identifier: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    image: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    license: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    link: Vec<BTreeMap<String, String>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    meta: Vec<BTreeMap<String, String>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    extension: BTreeMap<String, Vec<serde_json::Value>>,
+    #[serde(default = "Vec::new")]
+    track: Vec<T>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct JspfTrack {
+    #[serde(default, skip_serializing_if = "OneOrMany::is_empty")]
+    location: OneOrMany,
+    #[serde(default, skip_serializing_if = "OneOrMany::is_empty")]
+    identifier: OneOrMany,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    creator: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    annotation: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    info: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    image: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    album: Option<String>,
+    #[serde(rename = "trackNum", skip_serializing_if = "Option::is_none")]
+    track_num: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duration: Option<i32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    link: Vec<BTreeMap<String, String>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    meta: Vec<BTreeMap<String, String>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    extension: BTreeMap<String, Vec<serde_json::Value>>,
+}
+
+// the spec requires arrays, but some tools write a single string
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+enum OneOrMany {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl OneOrMany {
+    fn is_empty(&self) -> bool {
+        self.first().is_none()
+    }
+
+    fn first(&self) -> Option<&String> {
+        match self {
+            OneOrMany::One(value) => Some(value),
+            OneOrMany::Many(values) => values.first(),
+        }
+    }
+}
+
+// JSPF writes every link and meta element as an object with the rel as its only key
+fn read_relations(relations: Vec<BTreeMap<String, String>>) -> Vec<(String, String)> {
+    relations.into_iter().flatten().collect()
+}
+
+fn write_relations<'a>(
+    relations: impl Iterator<Item = (&'a String, &'a String)>,
+) -> Vec<BTreeMap<String, String>> {
+    relations
+        .map(|(rel, content)| {
+            let mut relation = BTreeMap::new();
+            relation.insert(rel.clone(), content.clone());
+            relation
+        })
+        .collect()
+}
+
+// JSPF groups the extensions by application, XSPF keeps the XML content as text
+fn read_extensions(extensions: BTreeMap<String, Vec<serde_json::Value>>) -> Vec<XspfExtension> {
+    extensions
+        .into_iter()
+        .flat_map(|(application, values)| {
+            values.into_iter().map(move |value| XspfExtension {
+                application: application.clone(),
+                content: match value {
+                    serde_json::Value::String(content) => content,
+                    value => value.to_string(),
+                },
+            })
+        })
+        .collect()
+}
+
+fn write_extensions(extensions: &[XspfExtension]) -> BTreeMap<String, Vec<serde_json::Value>> {
+    let mut written = BTreeMap::<String, Vec<serde_json::Value>>::new();
+    for extension in extensions {
+        let value = serde_json::from_str::<serde_json::Value>(&extension.content)
+            .ok()
+            .filter(|value| value.is_object() || value.is_array())
+            .unwrap_or_else(|| serde_json::Value::String(extension.content.clone()));
+        written
+            .entry(extension.application.clone())
+            .or_default()
+            .push(value);
+    }
+    written
+}
+
+impl Default for OneOrMany {
+    fn default() -> OneOrMany {
+        OneOrMany::Many(Vec::new())
+    }
+}
+
+impl From<Option<String>> for OneOrMany {
+    fn from(value: Option<String>) -> OneOrMany {
+        OneOrMany::Many(value.into_iter().collect())
+    }
+}
+
+// reads the JSPF playlist, tracks with invalid content are skipped and reported
+pub fn parse_jspf(content: &[u8]) -> Result<(Playlist, Vec<ParseError>), ParseError> {
+    let document: JspfDocument<serde_json::Value> = serde_json::from_slice(content)?;
+    let mut skipped = Vec::new();
+    let tracks = document
+        .playlist
+        .track
+        .into_iter()
+        .enumerate()
+        .filter_map(
+            |(index, track)| match serde_json::from_value::<JspfTrack>(track) {
+                Ok(track) => {
+                    let mut track = Track {
+                        location: track.location.first().cloned(),
+                        identifier: track.identifier.first().cloned(),
+                        title: track.title,
+                        artist: track.creator,
+                        annotation: track.annotation,
+                        info: track.info,
+                        image: track.image,
+                        album: track.album,
+                        track_number: track.track_num,
+                        duration: track.duration,
+                        links: read_relations(track.link)
+                            .into_iter()
+                            .map(|(rel, content)| XspfLink { rel, content })
+                            .collect(),
+                        meta: read_relations(track.meta)
+                            .into_iter()
+                            .map(|(rel, content)| XspfMeta { rel, content })
+                            .collect(),
+                        extensions: read_extensions(track.extension),
+                        ..Default::default()
+                    };
+                    track.read_known_meta();
+                    Some(track)
+                }
+                Err(error) => {
+                    skipped.push(
+                        ParseError::new(format!("skipped track {}, {}", index + 1, error))
+                            .in_element(Some("track")),
+                    );
+                    None
+                }
+            },
+        )
+        .collect();
+
+    let playlist = Playlist {
+        title: document.playlist.title,
+        creator: document.playlist.creator,
+        annotation: document.playlist.annotation,
+        info: document.playlist.info,
+        location: document.playlist.location,
+        identifier: document.playlist.identifier,
+        image: document.playlist.image,
+        date: document.playlist.date,
+        license: document.playlist.license,
+        links: read_relations(document.playlist.link)
+            .into_iter()
+            .map(|(rel, content)| XspfLink { rel, content })
+            .collect(),
+        meta: read_relations(document.playlist.meta)
+            .into_iter()
+            .map(|(rel, content)| XspfMeta { rel, content })
+            .collect(),
+        extensions: read_extensions(document.playlist.extension),
+        track_list: TrackList { tracks },
+    };
+    Ok((playlist, skipped))
+}
+
+pub fn write_jspf(playlist: &Playlist) -> String {
+    let track = playlist
+        .track_list
+        .tracks
+        .iter()
+        .cloned()
+        .map(|track| JspfTrack {
+            location: track.location.into(),
+            identifier: track.identifier.into(),
+            title: track.title,
+            creator: track.artist,
+            annotation: track.annotation,
+            info: track.info,
+            image: track.image,
+            album: track.album,
+            track_num: track.track_number,
+            duration: track.duration,
+            link: write_relations(track.links.iter().map(|link| (&link.rel, &link.content))),
+            meta: write_relations(track.meta.iter().map(|meta| (&meta.rel, &meta.content))),
+            extension: write_extensions(&track.extensions),
+        })
+        .collect();
+    let document = JspfDocument {
+        playlist: JspfPlaylist {
+            title: playlist.title.clone(),
+            creator: playlist.creator.clone(),
+            annotation: playlist.annotation.clone(),
+            info: playlist.info.clone(),
+            location: playlist.location.clone(),
+            identifier: playlist.identifier.clone(),
+            image: playlist.image.clone(),
+            date: playlist.date.clone(),
+            license: playlist.license.clone(),
+            link: write_relations(playlist.links.iter().map(|link| (&link.rel, &link.content))),
+            meta: write_relations(playlist.meta.iter().map(|meta| (&meta.rel, &meta.content))),
+            extension: write_extensions(&playlist.extensions),
+            track,
+        },
+    };
+    serde_json::to_string_pretty(&document).expect("serialize playlist")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn round_trip() {
+        let playlist = Playlist::with_tracks_and_title(
+            vec![Track {
+                location: Some("file:///music/song.mp3".to_string()),
+                title: Some("Title \"quoted\"".to_string()),
+                artist: Some("Artist".to_string()),
+                track_number: Some(3),
+                duration: Some(215_000),
+                ..Default::default()
+            }],
+            "Playlist".to_string(),
+        );
+
+        assert_eq!(
+            playlist,
+            parse_jspf(playlist.to_jspf().as_bytes()).unwrap().0
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn extensions() {
+        let mut playlist = Playlist::with_tracks_and_title(
+            vec![Track {
+                title: Some("Title".to_string()),
+                extensions: vec![
+                    XspfExtension {
+                        application: "http://example.com/app".to_string(),
+                        content: r#"{"rating":5}"#.to_string(),
+                    },
+                    XspfExtension {
+                        application: "http://example.com/app".to_string(),
+                        content: "<app:rating>5</app:rating>".to_string(),
+                    },
+                ],
+                ..Default::default()
+            }],
+            "Playlist".to_string(),
+        );
+        playlist.extensions = vec![XspfExtension {
+            application: "http://example.com/other".to_string(),
+            content: r#"["a","b"]"#.to_string(),
+        }];
+
+        let written = playlist.to_jspf();
+        assert!(written.contains(r#""rating": 5"#));
+        assert_eq!(playlist, parse_jspf(written.as_bytes()).unwrap().0);
+    }
+
+    #[wasm_bindgen_test]
+    fn single_location_string() {
+        let playlist =
+            parse_jspf(br#"{"playlist": {"track": [{"location": "song.mp3", "title": "Title"}]}}"#)
+                .unwrap()
+                .0;
+
+        assert_eq!(
+            Some("song.mp3".to_string()),
+            playlist.track_list.tracks[0].location
+        );
+    }
+}
@@ -4,18 +4,43 @@
 extern crate serde_derive;
 
 mod app;
+mod artists;
+mod audio_tags;
 mod connect;
+mod csv_import;
+mod csv_mapping;
 mod download_file;
 mod import;
+mod import_summary;
+mod itunes;
+mod json_export;
+mod jspf;
+mod m3u;
+mod match_report;
+mod match_settings;
+mod matcher;
+mod merge;
+mod normalize;
+mod parse_error;
+mod path_metadata;
+mod playlist_format;
 mod playlist_types;
+mod pls;
+mod search_query;
 mod spotify_types;
+mod text_diff;
+mod text_encoding;
 mod track_item;
 mod track_list;
+mod wpl;
+mod xspf;
 
 pub use app::App;
 pub use connect::Connect;
+pub use csv_mapping::CsvMapping;
 pub use download_file::download_file;
 pub use import::Import;
+pub use match_settings::MatchSettingsPanel;
 pub use track_item::TrackItem;
 pub use track_list::TrackList;
 
@@ -0,0 +1,219 @@
+use crate::playlist_types::*;
+
+pub fn parse_m3u(content: &str) -> Playlist {
+    let mut playlist = Playlist::default();
+    let mut pending = Track::default();
+
+    for line in content.lines().map(str::trim) {
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(directive) = line.strip_prefix('#') {
+            if let Some(extinf) = directive.strip_prefix("EXTINF:") {
+                let (duration, display_name) = split_extinf(extinf);
+                let (artist, title) = split_display_name(display_name);
+                pending.duration = duration;
+                pending.artist = artist;
+                pending.title = title;
+            } else if let Some(artist) = directive.strip_prefix("EXTART:") {
+                pending.artist = non_empty(artist);
+            } else if let Some(album) = directive.strip_prefix("EXTALB:") {
+                pending.album = non_empty(album);
+            } else if let Some(title) = directive.strip_prefix("PLAYLIST:") {
+                playlist.title = non_empty(title);
+            }
+            // all other lines starting with '#' are comments or unsupported directives
+            continue;
+        }
+
+        pending.location = Some(line.to_owned());
+        playlist
+            .track_list
+            .tracks
+            .push(std::mem::take(&mut pending));
+    }
+
+    playlist
+}
+
+pub fn write_m3u8(playlist: &Playlist) -> String {
+    let mut lines = vec!["#EXTM3U".to_owned()];
+    if let Some(title) = &playlist.title {
+        lines.push(format!("#PLAYLIST:{}", single_line(title)));
+    }
+
+    for track in &playlist.track_list.tracks {
+        let display_name = match (&track.artist, &track.title) {
+            (Some(artist), Some(title)) => format!("{} - {}", artist, title),
+            (None, Some(title)) => title.clone(),
+            (Some(artist), None) => artist.clone(),
+            (None, None) => String::new(),
+        };
+        // the name would be read as the location of the track, so it is only kept as a comment
+        let path = match track.location.as_ref().or(track.identifier.as_ref()) {
+            Some(path) => path,
+            None => {
+                lines.push(format!("# {}", single_line(&display_name)));
+                continue;
+            }
+        };
+        let seconds = track
+            .duration
+            .map_or(-1, |duration| (duration as f64 / 1_000.0).round() as i32);
+        lines.push(format!(
+            "#EXTINF:{},{}",
+            seconds,
+            single_line(&display_name)
+        ));
+        if let Some(album) = &track.album {
+            lines.push(format!("#EXTALB:{}", single_line(album)));
+        }
+        lines.push(single_line(path));
+    }
+
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+fn single_line(value: &str) -> String {
+    value.replace(['\r', '\n'], " ")
+}
+
+// splits `123 key="value",Display name` into the duration in milliseconds and the display name
+fn split_extinf(extinf: &str) -> (Option<i32>, &str) {
+    let mut in_quotes = false;
+    let comma = extinf.char_indices().find_map(|(index, c)| match c {
+        '"' => {
+            in_quotes = !in_quotes;
+            None
+        }
+        ',' if !in_quotes => Some(index),
+        _ => None,
+    });
+    let (info, display_name) = match comma {
+        Some(index) => (&extinf[..index], extinf[index + 1..].trim()),
+        None => (extinf, ""),
+    };
+
+    let duration = info
+        .split_whitespace()
+        .next()
+        .and_then(|seconds| seconds.parse::<f64>().ok())
+        .filter(|seconds| *seconds > 0.0)
+        .map(|seconds| (seconds * 1_000.0).round() as i32);
+
+    (duration, display_name)
+}
+
+// splits `Artist - Title` into artist and title
+pub fn split_display_name(display_name: &str) -> (Option<String>, Option<String>) {
+    match display_name.split_once(" - ") {
+        Some((artist, title)) => (non_empty(artist), non_empty(title)),
+        None => (None, non_empty(display_name)),
+    }
+}
+
+pub fn non_empty(value: &str) -> Option<String> {
+    let value = value.trim();
+    if value.is_empty() {
+        None
+    } else {
+        Some(value.to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn extended() {
+        let playlist = parse_m3u(
+            "#EXTM3U\n#PLAYLIST:Mix\n#EXTINF:215,Artist - Title - Live\nmusic/01.mp3\n\n#EXTINF:-1 tvg-name=\"a,b\",Radio\nhttp://radio/stream\n",
+        );
+
+        assert_eq!(Some("Mix".to_string()), playlist.title);
+        assert_eq!(
+            vec![
+                Track {
+                    location: Some("music/01.mp3".to_string()),
+                    artist: Some("Artist".to_string()),
+                    title: Some("Title - Live".to_string()),
+                    duration: Some(215_000),
+                    ..Default::default()
+                },
+                Track {
+                    location: Some("http://radio/stream".to_string()),
+                    title: Some("Radio".to_string()),
+                    ..Default::default()
+                },
+            ],
+            playlist.track_list.tracks
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn write_round_trip() {
+        let playlist = Playlist::with_tracks_and_title(
+            vec![
+                Track {
+                    location: Some("music/01.mp3".to_string()),
+                    artist: Some("Artist".to_string()),
+                    title: Some("Title".to_string()),
+                    album: Some("Album".to_string()),
+                    duration: Some(215_000),
+                    ..Default::default()
+                },
+                Track {
+                    location: Some("http://radio/stream".to_string()),
+                    title: Some("Radio".to_string()),
+                    ..Default::default()
+                },
+            ],
+            "Mix".to_string(),
+        );
+
+        let written = write_m3u8(&playlist);
+        assert!(written.contains("#EXTINF:-1,Radio\n"));
+        assert_eq!(playlist, parse_m3u(&written));
+    }
+
+    #[wasm_bindgen_test]
+    fn write_without_location() {
+        let located = Track {
+            location: Some("music/01.mp3".to_string()),
+            title: Some("Located".to_string()),
+            ..Default::default()
+        };
+        let playlist = Playlist::with_tracks_and_title(
+            vec![
+                Track {
+                    artist: Some("Artist".to_string()),
+                    title: Some("Title".to_string()),
+                    duration: Some(215_000),
+                    ..Default::default()
+                },
+                located.clone(),
+            ],
+            "Mix".to_string(),
+        );
+
+        let written = write_m3u8(&playlist);
+        assert!(written.contains("\n# Artist - Title\n"));
+        assert_eq!(vec![located], parse_m3u(&written).track_list.tracks);
+    }
+
+    #[wasm_bindgen_test]
+    fn plain() {
+        let playlist = parse_m3u("C:\\Music\\song.mp3\r\n/home/music/other.flac\r\n");
+
+        assert_eq!(2, playlist.track_list.tracks.len());
+        assert_eq!(
+            Some("/home/music/other.flac".to_string()),
+            playlist.track_list.tracks[1].location
+        );
+        assert_eq!(None, playlist.track_list.tracks[1].title);
+    }
+}
@@ -0,0 +1,170 @@
+use crate::matcher::MatchSettings;
+use crate::playlist_types::*;
+use strum_macros::{Display, EnumIter, EnumString};
+
+#[derive(Debug, Clone, Copy, PartialEq, Display, EnumIter, EnumString)]
+pub enum ReportFormat {
+    #[strum(serialize = "CSV")]
+    Csv,
+    #[strum(serialize = "JSON")]
+    Json,
+}
+
+impl ReportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ReportFormat::Csv => "csv",
+            ReportFormat::Json => "json",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Selection {
+    Automatic,
+    Manual,
+}
+
+// one row per input track, the Spotify columns stay empty for unmatched tracks
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+pub struct MatchReportRow {
+    pub input_title: Option<String>,
+    pub input_artist: Option<String>,
+    pub input_album: Option<String>,
+    pub input_duration_ms: Option<i32>,
+    pub input_location: Option<String>,
+    pub input_identifier: Option<String>,
+    pub spotify_uri: Option<String>,
+    pub spotify_title: Option<String>,
+    pub spotify_artist: Option<String>,
+    pub spotify_album: Option<String>,
+    pub spotify_duration_ms: Option<i32>,
+    pub score: Option<f64>,
+    pub title_score: Option<f64>,
+    pub artist_score: Option<f64>,
+    pub album_score: Option<f64>,
+    pub duration_score: Option<f64>,
+    pub track_number_score: Option<f64>,
+    // the reasons the score was lowered, separated by `; `
+    pub penalties: Option<String>,
+    pub selection: Option<Selection>,
+    pub query: Option<String>,
+}
+
+impl MatchReportRow {
+    // the Spotify track is only known if it was loaded, a stored mapping might not be
+    pub fn new(
+        in_track: &Track,
+        output_id: Option<&str>,
+        out_track: Option<&Track>,
+        selection: Option<Selection>,
+        query: Option<String>,
+        settings: &MatchSettings,
+    ) -> MatchReportRow {
+        let similarity = out_track.map(|out_track| in_track.similarity(out_track, settings));
+        let score = |value: f64| {
+            Some((value * 1_000.0).round() / 1_000.0).filter(|value| value.is_finite())
+        };
+        MatchReportRow {
+            input_title: in_track.title.clone(),
+            input_artist: in_track.artist.clone(),
+            input_album: in_track.album.clone(),
+            input_duration_ms: in_track.duration,
+            input_location: in_track.location.clone(),
+            input_identifier: in_track.identifier.clone(),
+            spotify_uri: output_id.map(str::to_owned),
+            spotify_title: out_track.and_then(|out_track| out_track.title.clone()),
+            spotify_artist: out_track.and_then(|out_track| out_track.artist.clone()),
+            spotify_album: out_track.and_then(|out_track| out_track.album.clone()),
+            spotify_duration_ms: out_track.and_then(|out_track| out_track.duration),
+            score: similarity
+                .as_ref()
+                .and_then(|similarity| score(similarity.score)),
+            title_score: similarity
+                .as_ref()
+                .and_then(|similarity| score(similarity.title?)),
+            artist_score: similarity
+                .as_ref()
+                .and_then(|similarity| score(similarity.artist?)),
+            album_score: similarity
+                .as_ref()
+                .and_then(|similarity| score(similarity.album?)),
+            duration_score: similarity
+                .as_ref()
+                .and_then(|similarity| score(similarity.duration?)),
+            track_number_score: similarity
+                .as_ref()
+                .and_then(|similarity| score(similarity.track_number?)),
+            penalties: similarity
+                .as_ref()
+                .filter(|similarity| !similarity.penalties.is_empty())
+                .map(|similarity| {
+                    similarity
+                        .penalties
+                        .iter()
+                        .map(|penalty| penalty.reason.as_str())
+                        .collect::<Vec<_>>()
+                        .join("; ")
+                }),
+            selection: output_id.and(selection),
+            query: output_id.and(query),
+        }
+    }
+}
+
+pub fn write_report(rows: &[MatchReportRow], format: ReportFormat) -> String {
+    match format {
+        ReportFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(Vec::new());
+            for row in rows {
+                writer.serialize(row).expect("write row");
+            }
+            let content = writer.into_inner().expect("flush rows");
+            String::from_utf8(content).expect("valid UTF-8")
+        }
+        ReportFormat::Json => serde_json::to_string_pretty(rows).expect("serialize rows"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn csv_report() {
+        let in_track = Track {
+            title: Some("Title".to_string()),
+            artist: Some("Artist".to_string()),
+            duration: Some(200_000),
+            ..Default::default()
+        };
+        let out_track = Track {
+            identifier: Some("spotify:track:1".to_string()),
+            title: Some("Title".to_string()),
+            artist: Some("Artist".to_string()),
+            album: Some("Album".to_string()),
+            duration: Some(200_000),
+            ..Default::default()
+        };
+        let rows = vec![
+            MatchReportRow::new(
+                &in_track,
+                Some("spotify:track:1"),
+                Some(&out_track),
+                Some(Selection::Manual),
+                Some("Artist Title".to_string()),
+                &MatchSettings::default(),
+            ),
+            MatchReportRow::new(&in_track, None, None, None, None, &MatchSettings::default()),
+        ];
+
+        let report = write_report(&rows, ReportFormat::Csv);
+        let lines = report.lines().collect::<Vec<_>>();
+        assert_eq!(3, lines.len());
+        assert!(lines[0].starts_with("input_title,input_artist,"));
+        assert!(lines[1].ends_with(",1.0,1.0,1.0,,1.0,,,manual,Artist Title"));
+        assert!(lines[2].ends_with(",,,,,,,,,,,,"));
+    }
+}
@@ -0,0 +1,127 @@
+use crate::matcher::*;
+use strum::IntoEnumIterator;
+use yew::{html::Html, prelude::*, Properties};
+
+use std::str::FromStr;
+
+pub struct MatchSettingsPanel {
+    link: ComponentLink<Self>,
+    props: Props,
+}
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct Props {
+    pub settings: MatchSettings,
+    pub onchange: Callback<MatchSettings>,
+}
+
+#[derive(Clone, Copy)]
+pub enum WeightField {
+    Title,
+    Artist,
+    Album,
+    Duration,
+    TrackNumber,
+}
+
+pub enum Msg {
+    AlgorithmSelected(MatchAlgorithm),
+    WeightChanged(WeightField, f64),
+    Reset,
+    Noop,
+}
+
+impl Component for MatchSettingsPanel {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        MatchSettingsPanel { link, props }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        let mut settings = self.props.settings;
+        match msg {
+            Msg::AlgorithmSelected(algorithm) => settings.algorithm = algorithm,
+            Msg::WeightChanged(field, weight) => {
+                let weights = &mut settings.weights;
+                match field {
+                    WeightField::Title => weights.title = weight,
+                    WeightField::Artist => weights.artist = weight,
+                    WeightField::Album => weights.album = weight,
+                    WeightField::Duration => weights.duration = weight,
+                    WeightField::TrackNumber => weights.track_number = weight,
+                }
+            }
+            Msg::Reset => settings = MatchSettings::default(),
+            Msg::Noop => return false,
+        }
+        if settings != self.props.settings {
+            self.props.onchange.emit(settings);
+        }
+        false
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.props = props;
+        true
+    }
+
+    fn view(&self) -> Html {
+        let onchange_algorithm = self
+            .link
+            .callback(|event: yew::html::ChangeData| match event {
+                yew::html::ChangeData::Select(select) => MatchAlgorithm::from_str(&select.value())
+                    .map_or(Msg::Noop, Msg::AlgorithmSelected),
+                _ => Msg::Noop,
+            });
+        let onclick_reset = self.link.callback(|_| Msg::Reset);
+
+        let weights = self.props.settings.weights;
+        let render_weight = |name: &str, field: WeightField, weight: f64| {
+            let onchange = self
+                .link
+                .callback(move |event: yew::html::ChangeData| match event {
+                    yew::html::ChangeData::Value(value) => value
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|weight| weight.is_finite() && *weight >= 0.0)
+                        .map_or(Msg::Noop, |weight| Msg::WeightChanged(field, weight)),
+                    _ => Msg::Noop,
+                });
+            html! {
+                <label class="inline weight">
+                    {name}
+                    <input type="number" min="0" step="0.5" value=weight.to_string() onchange=onchange/>
+                </label>
+            }
+        };
+
+        html! {
+            <details class="match-settings">
+                <summary>{"Matching settings"}</summary>
+                <div>
+                    <span class="form">{"Algorithm:"}</span>
+                    <select onchange=onchange_algorithm>
+                        {
+                            for MatchAlgorithm::iter().map(|algorithm| html! {
+                                <option value={algorithm.to_string()} selected={algorithm == self.props.settings.algorithm}>
+                                    {algorithm.to_string()}
+                                </option>
+                            })
+                        }
+                    </select>
+                </div>
+                <div>
+                    <span class="form">{"Weights:"}</span>
+                    {render_weight("Title", WeightField::Title, weights.title)}
+                    {render_weight("Artist", WeightField::Artist, weights.artist)}
+                    {render_weight("Album", WeightField::Album, weights.album)}
+                    {render_weight("Duration", WeightField::Duration, weights.duration)}
+                    {render_weight("Track number", WeightField::TrackNumber, weights.track_number)}
+                    <button onclick=onclick_reset>{"Reset"}</button>
+                </div>
+            </details>
+        }
+    }
+}
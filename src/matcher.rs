@@ -0,0 +1,143 @@
+use strum_macros::{Display, EnumIter, EnumString};
+
+use std::collections::BTreeSet;
+
+// compares two normalized strings, 1.0 means equal
+pub trait Matcher {
+    fn similarity(&self, a: &str, b: &str) -> f64;
+}
+
+pub struct Jaro;
+
+impl Matcher for Jaro {
+    fn similarity(&self, a: &str, b: &str) -> f64 {
+        strsim::jaro(a, b)
+    }
+}
+
+pub struct JaroWinkler;
+
+impl Matcher for JaroWinkler {
+    fn similarity(&self, a: &str, b: &str) -> f64 {
+        strsim::jaro_winkler(a, b)
+    }
+}
+
+pub struct NormalizedLevenshtein;
+
+impl Matcher for NormalizedLevenshtein {
+    fn similarity(&self, a: &str, b: &str) -> f64 {
+        strsim::normalized_levenshtein(a, b)
+    }
+}
+
+// ignores the order of words and words that only appear in one of the strings,
+// so `Symphony No. 5 in C Minor` matches `Beethoven: Symphony No. 5`
+pub struct TokenSet;
+
+impl Matcher for TokenSet {
+    fn similarity(&self, a: &str, b: &str) -> f64 {
+        let tokens_a = a.split_whitespace().collect::<BTreeSet<_>>();
+        let tokens_b = b.split_whitespace().collect::<BTreeSet<_>>();
+        let join = |tokens: Vec<&str>| tokens.join(" ");
+
+        let common = join(tokens_a.intersection(&tokens_b).copied().collect());
+        let with_rest = |rest: BTreeSet<&str>| {
+            join(
+                common
+                    .split_whitespace()
+                    .chain(rest.iter().copied())
+                    .collect(),
+            )
+        };
+        let all_a = with_rest(tokens_a.difference(&tokens_b).copied().collect());
+        let all_b = with_rest(tokens_b.difference(&tokens_a).copied().collect());
+
+        let ratio = |a: &str, b: &str| strsim::normalized_levenshtein(a, b);
+        let mut similarity = ratio(&all_a, &all_b);
+        if !common.is_empty() {
+            similarity = similarity
+                .max(ratio(&common, &all_a))
+                .max(ratio(&common, &all_b));
+        }
+        similarity
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Display, EnumIter, EnumString, Serialize, Deserialize)]
+pub enum MatchAlgorithm {
+    #[strum(serialize = "Jaro")]
+    Jaro,
+    #[strum(serialize = "Jaro-Winkler")]
+    JaroWinkler,
+    #[strum(serialize = "Normalized Levenshtein")]
+    NormalizedLevenshtein,
+    #[strum(serialize = "Token set")]
+    TokenSet,
+}
+
+impl MatchAlgorithm {
+    pub fn matcher(self) -> &'static dyn Matcher {
+        match self {
+            MatchAlgorithm::Jaro => &Jaro,
+            MatchAlgorithm::JaroWinkler => &JaroWinkler,
+            MatchAlgorithm::NormalizedLevenshtein => &NormalizedLevenshtein,
+            MatchAlgorithm::TokenSet => &TokenSet,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FieldWeights {
+    pub title: f64,
+    pub artist: f64,
+    pub album: f64,
+    pub duration: f64,
+    // album tracks are often numbered differently in compilations, so it isn't used by default
+    #[serde(default)]
+    pub track_number: f64,
+}
+
+impl Default for FieldWeights {
+    fn default() -> FieldWeights {
+        FieldWeights {
+            title: 2.0,
+            artist: 2.0,
+            album: 1.0,
+            duration: 5.0,
+            track_number: 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MatchSettings {
+    pub algorithm: MatchAlgorithm,
+    pub weights: FieldWeights,
+}
+
+impl Default for MatchSettings {
+    fn default() -> MatchSettings {
+        MatchSettings {
+            algorithm: MatchAlgorithm::Jaro,
+            weights: FieldWeights::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn token_set() {
+        assert!((TokenSet.similarity("b a", "a b") - 1.0).abs() < 1e-9);
+        assert!(
+            (TokenSet.similarity("symphony no 5", "beethoven symphony no 5") - 1.0).abs() < 1e-9
+        );
+        assert!(TokenSet.similarity("symphony no 5", "symphony no 9") < 1.0);
+        assert!(TokenSet.similarity("abc", "xyz") < 0.5);
+        assert!((TokenSet.similarity("", "") - 1.0).abs() < 1e-9);
+    }
+}
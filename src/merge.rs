@@ -0,0 +1,138 @@
+use crate::playlist_types::*;
+use strum_macros::{Display, EnumIter, EnumString};
+
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Display, EnumIter, EnumString)]
+pub enum MergeMode {
+    #[strum(serialize = "Concatenate")]
+    Concatenate,
+    #[strum(serialize = "Interleave")]
+    Interleave,
+}
+
+// tracks that appear in several playlists are only kept once and list all of their sources
+pub fn merge_playlists(playlists: Vec<(String, Vec<Track>)>, mode: MergeMode) -> Vec<Track> {
+    let mut queues = playlists
+        .into_iter()
+        .enumerate()
+        .map(|(index, (source, tracks))| (index, source, tracks.into_iter()))
+        .collect::<Vec<_>>();
+
+    let mut ordered = Vec::new();
+    match mode {
+        MergeMode::Concatenate => {
+            for (index, source, tracks) in queues.iter_mut() {
+                ordered.extend(tracks.map(|track| (*index, source.clone(), track)));
+            }
+        }
+        MergeMode::Interleave => loop {
+            let round = queues
+                .iter_mut()
+                .filter_map(|(index, source, tracks)| {
+                    Some((*index, source.clone(), tracks.next()?))
+                })
+                .collect::<Vec<_>>();
+            if round.is_empty() {
+                break;
+            }
+            ordered.extend(round);
+        },
+    }
+
+    let mut merged: Vec<Track> = Vec::new();
+    let mut positions = HashMap::<String, (usize, usize)>::new();
+    for (index, source, mut track) in ordered {
+        // the same MusicBrainz recording is a duplicate, even if the playlists spell it differently
+        let keys = std::iter::once(track.id())
+            .chain(
+                track
+                    .musicbrainz_id
+                    .as_ref()
+                    .map(|mbid| format!("musicbrainz:{}", mbid)),
+            )
+            .collect::<Vec<_>>();
+        match keys.iter().find_map(|key| positions.get(key)) {
+            // duplicates within the same playlist are kept
+            Some((position, first_index)) if *first_index != index => {
+                let sources = &mut merged[*position].sources;
+                if !sources.contains(&source) {
+                    sources.push(source);
+                }
+            }
+            _ => {
+                for key in keys {
+                    positions.entry(key).or_insert((merged.len(), index));
+                }
+                track.sources = vec![source];
+                merged.push(track);
+            }
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    fn track(title: &str) -> Track {
+        Track {
+            title: Some(title.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn titles(tracks: &[Track]) -> Vec<&str> {
+        tracks
+            .iter()
+            .map(|track| track.title.as_deref().unwrap_or_default())
+            .collect()
+    }
+
+    #[wasm_bindgen_test]
+    fn merge() {
+        let playlists = || {
+            vec![
+                ("One".to_string(), vec![track("A"), track("B"), track("C")]),
+                ("Two".to_string(), vec![track("D"), track("A")]),
+            ]
+        };
+
+        let concatenated = merge_playlists(playlists(), MergeMode::Concatenate);
+        assert_eq!(vec!["A", "B", "C", "D"], titles(&concatenated));
+        assert_eq!(
+            vec!["One".to_string(), "Two".to_string()],
+            concatenated[0].sources
+        );
+        assert_eq!(vec!["Two".to_string()], concatenated[3].sources);
+
+        let interleaved = merge_playlists(playlists(), MergeMode::Interleave);
+        assert_eq!(vec!["A", "D", "B", "C"], titles(&interleaved));
+    }
+
+    #[wasm_bindgen_test]
+    fn merge_musicbrainz_recordings() {
+        let recording = |title: &str| Track {
+            musicbrainz_id: Some("b1a9c0e9-d987-4042-ae91-78d6a3267d69".to_string()),
+            ..track(title)
+        };
+        let merged = merge_playlists(
+            vec![
+                ("One".to_string(), vec![recording("A")]),
+                (
+                    "Two".to_string(),
+                    vec![recording("A (Remastered)"), track("B")],
+                ),
+            ],
+            MergeMode::Concatenate,
+        );
+
+        assert_eq!(vec!["A", "B"], titles(&merged));
+        assert_eq!(
+            vec!["One".to_string(), "Two".to_string()],
+            merged[0].sources
+        );
+    }
+}
@@ -0,0 +1,113 @@
+// variants of apostrophes, quotes and dashes are replaced before the decomposition,
+// which would turn some of them into a space and a combining mark
+fn unify_punctuation(c: char) -> char {
+    match c {
+        '\u{2018}' | '\u{2019}' | '\u{201a}' | '\u{201b}' | '\u{2032}' | '\u{02bc}'
+        | '\u{00b4}' | '`' => '\'',
+        '\u{201c}' | '\u{201d}' | '\u{201e}' | '\u{201f}' | '\u{2033}' | '\u{00ab}'
+        | '\u{00bb}' => '"',
+        '\u{2010}'..='\u{2015}' | '\u{2212}' => '-',
+        c => c,
+    }
+}
+
+fn is_combining_mark(c: char) -> bool {
+    matches!(c,
+        '\u{0300}'..='\u{036f}'
+        | '\u{1ab0}'..='\u{1aff}'
+        | '\u{1dc0}'..='\u{1dff}'
+        | '\u{20d0}'..='\u{20ff}'
+        | '\u{fe20}'..='\u{fe2f}')
+}
+
+// removes the marks of decomposed characters and spells out letters that NFKD keeps
+fn strip_diacritics(decomposed: &str) -> String {
+    let mut stripped = String::with_capacity(decomposed.len());
+    for c in decomposed.chars() {
+        match c {
+            'æ' => stripped.push_str("ae"),
+            'Æ' => stripped.push_str("AE"),
+            'œ' => stripped.push_str("oe"),
+            'Œ' => stripped.push_str("OE"),
+            'ß' => stripped.push_str("ss"),
+            'þ' => stripped.push_str("th"),
+            'Þ' => stripped.push_str("TH"),
+            'ø' => stripped.push('o'),
+            'Ø' => stripped.push('O'),
+            'đ' => stripped.push('d'),
+            'Đ' => stripped.push('D'),
+            'ł' => stripped.push('l'),
+            'Ł' => stripped.push('L'),
+            c if is_combining_mark(c) => {}
+            c => stripped.push(c),
+        }
+    }
+    stripped
+}
+
+// NFKD also maps full-width characters and ligatures like `ﬁ` to their plain form,
+// NFC puts back together what was decomposed without a mark, like Hangul syllables or kana
+pub fn fold(text: &str) -> String {
+    let unified = text.chars().map(unify_punctuation).collect::<String>();
+    let decomposed = String::from(js_sys::JsString::from(unified.as_str()).normalize("NFKD"));
+    let stripped = strip_diacritics(&decomposed);
+    String::from(js_sys::JsString::from(stripped.as_str()).normalize("NFC"))
+}
+
+// the text is only compared, so case, punctuation and the spelling of "and" don't matter
+pub fn fold_for_matching(text: &str) -> String {
+    let folded = fold(text).to_lowercase();
+    let mut words = String::with_capacity(folded.len());
+    for c in folded.chars() {
+        match c {
+            '\'' => {}
+            '&' | '+' => words.push_str(" and "),
+            c if c.is_alphanumeric() => words.push(c),
+            _ => words.push(' '),
+        }
+    }
+    words.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn diacritics() {
+        assert_eq!("Beyonce", fold("Beyoncé"));
+        assert_eq!("Sigur Ros", fold("Sigur Rós"));
+        assert_eq!("Motorhead", fold("Motörhead"));
+        assert_eq!("Mgla", fold("Mgła"));
+        assert_eq!("Strasse", fold("Straße"));
+    }
+
+    #[wasm_bindgen_test]
+    fn compatibility_characters() {
+        assert_eq!("ABC 123", fold("ＡＢＣ　１２３"));
+        assert_eq!("fire", fold("ﬁre"));
+        assert_eq!("Don't Stop - Live", fold("Don’t Stop – Live"));
+    }
+
+    #[wasm_bindgen_test]
+    fn composed_scripts() {
+        assert_eq!("방탄소년단", fold("방탄소년단"));
+        assert_eq!("ガールズ", fold("ガールズ"));
+        assert_eq!("ガールズ", fold("ｶﾞｰﾙｽﾞ"));
+    }
+
+    #[wasm_bindgen_test]
+    fn matching() {
+        assert_eq!(
+            fold_for_matching("Simon and Garfunkel"),
+            fold_for_matching("Simon & Garfunkel")
+        );
+        assert_eq!(
+            fold_for_matching("Don't Stop"),
+            fold_for_matching("DON’T STOP")
+        );
+        assert_eq!("ac dc", fold_for_matching("AC/DC"));
+        assert_eq!("beyonce halo", fold_for_matching("Beyoncé — Halo!"));
+    }
+}
@@ -0,0 +1,81 @@
+use xml::common::{Position, TextPosition};
+
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ParseError {
+    pub message: String,
+    pub line: Option<u64>,
+    pub column: Option<u64>,
+    pub element: Option<String>,
+}
+
+impl ParseError {
+    pub fn new(message: impl Into<String>) -> ParseError {
+        ParseError {
+            message: message.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn at(mut self, position: TextPosition) -> ParseError {
+        // xml-rs counts from 0
+        self.line = Some(position.row + 1);
+        self.column = Some(position.column + 1);
+        self
+    }
+
+    pub fn in_element(mut self, element: Option<&str>) -> ParseError {
+        self.element = element.map(ToOwned::to_owned);
+        self
+    }
+
+    pub fn from_xml(error: &xml::reader::Error, element: Option<&str>) -> ParseError {
+        ParseError::new(error.msg())
+            .at(error.position())
+            .in_element(element)
+    }
+}
+
+impl From<xml::reader::Error> for ParseError {
+    fn from(error: xml::reader::Error) -> ParseError {
+        ParseError::from_xml(&error, None)
+    }
+}
+
+impl From<serde_json::Error> for ParseError {
+    fn from(error: serde_json::Error) -> ParseError {
+        let message = error.to_string();
+        ParseError {
+            // the position is already reported separately
+            message: message
+                .split(" at line ")
+                .next()
+                .unwrap_or_default()
+                .to_owned(),
+            line: Some(error.line() as u64),
+            column: Some(error.column() as u64),
+            element: None,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.line, self.column) {
+            (Some(line), Some(column)) => write!(f, "line {}, column {}", line, column)?,
+            (Some(line), None) => write!(f, "line {}", line)?,
+            _ => {}
+        }
+        if let Some(element) = self.element.as_ref() {
+            if self.line.is_some() {
+                write!(f, ", ")?;
+            }
+            write!(f, "<{}>", element)?;
+        }
+        if self.line.is_some() || self.element.is_some() {
+            write!(f, ": ")?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
@@ -0,0 +1,225 @@
+use crate::m3u::non_empty;
+use crate::playlist_types::*;
+
+use percent_encoding::percent_decode_str;
+
+pub const DEFAULT_PATH_PATTERNS: [&str; 5] = [
+    "{artist}/{album}/{track} - {title}",
+    "{track} - {artist} - {title}",
+    "{track} - {title}",
+    "{artist} - {title}",
+    "{title}",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Literal(String),
+    Field(String),
+}
+
+// turns `file:///music/Artist/03%20-%20Title.flac` into `/music/Artist/03 - Title`
+pub fn location_path(location: &str) -> String {
+    let path = match location.find("://") {
+        Some(index) => {
+            // skip the scheme and the host
+            let path = &location[index + 3..];
+            let path = &path[path.find('/').unwrap_or(path.len())..];
+            if location.starts_with("file:") {
+                path
+            } else {
+                path.split(['?', '#']).next().unwrap_or_default()
+            }
+        }
+        None => location,
+    };
+    let path = percent_decode_str(path)
+        .decode_utf8_lossy()
+        .replace('\\', "/");
+
+    // remove the file extension
+    match path.rsplit_once('.') {
+        Some((stem, extension))
+            if !extension.is_empty()
+                && extension.len() <= 5
+                && extension.chars().all(|c| c.is_ascii_alphanumeric()) =>
+        {
+            stem.to_owned()
+        }
+        _ => path,
+    }
+}
+
+fn parse_pattern(segment: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut rest = segment;
+    while !rest.is_empty() {
+        match (rest.find('{'), rest.find('}')) {
+            (Some(0), Some(end)) => {
+                tokens.push(Token::Field(rest[1..end].trim().to_lowercase()));
+                rest = &rest[end + 1..];
+            }
+            (Some(start), _) if start > 0 => {
+                tokens.push(Token::Literal(rest[..start].to_owned()));
+                rest = &rest[start..];
+            }
+            _ => {
+                tokens.push(Token::Literal(rest.to_owned()));
+                rest = "";
+            }
+        }
+    }
+    tokens
+}
+
+fn match_tokens(tokens: &[Token], text: &str, values: &mut Vec<(String, String)>) -> bool {
+    match tokens.split_first() {
+        None => text.is_empty(),
+        Some((Token::Literal(literal), rest)) => {
+            text.starts_with(literal.as_str()) && match_tokens(rest, &text[literal.len()..], values)
+        }
+        Some((Token::Field(field), rest)) => {
+            // prefer the shortest value, so that `{artist} - {title}` splits at the first dash
+            let ends = text
+                .char_indices()
+                .skip(1)
+                .map(|(index, _)| index)
+                .chain(std::iter::once(text.len()));
+            for end in ends {
+                let value = text[..end].trim();
+                if value.is_empty() || (field == "track" && value.parse::<i32>().is_err()) {
+                    continue;
+                }
+                values.push((field.clone(), value.to_owned()));
+                if match_tokens(rest, &text[end..], values) {
+                    return true;
+                }
+                values.pop();
+            }
+            false
+        }
+    }
+}
+
+// the pattern is matched against the last segments of the path
+fn match_pattern(pattern: &str, path: &str) -> Option<Vec<(String, String)>> {
+    let pattern_segments = pattern.split('/').collect::<Vec<_>>();
+    let path_segments = path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>();
+    let path_segments =
+        path_segments.get(path_segments.len().checked_sub(pattern_segments.len())?..)?;
+
+    let mut values = Vec::new();
+    for (pattern_segment, path_segment) in pattern_segments.iter().zip(path_segments) {
+        if !match_tokens(&parse_pattern(pattern_segment), path_segment, &mut values) {
+            return None;
+        }
+    }
+    Some(values)
+}
+
+// fills in the fields that are missing from the track, the result of earlier patterns is replaced
+pub fn infer_from_path(track: &mut Track, patterns: &[String]) {
+    for field in std::mem::take(&mut track.inferred) {
+        match field {
+            TrackField::Title => track.title = None,
+            TrackField::Artist => track.artist = None,
+            TrackField::Album => track.album = None,
+            TrackField::TrackNumber => track.track_number = None,
+            _ => {}
+        }
+    }
+
+    // only tracks that cannot be searched for are completed
+    if track.title.is_some() && track.artist.is_some() {
+        return;
+    }
+    let path = match track.location.as_deref() {
+        Some(location) => location_path(location),
+        None => return,
+    };
+    let values = match patterns
+        .iter()
+        .find_map(|pattern| match_pattern(pattern, &path))
+    {
+        Some(values) => values,
+        None => return,
+    };
+
+    for (field, value) in values {
+        let (field, missing) = match field.as_str() {
+            "title" => (TrackField::Title, track.title.is_none()),
+            "artist" => (TrackField::Artist, track.artist.is_none()),
+            "album" => (TrackField::Album, track.album.is_none()),
+            "track" => (TrackField::TrackNumber, track.track_number.is_none()),
+            _ => continue,
+        };
+        if !missing {
+            continue;
+        }
+        match field {
+            TrackField::Title => track.title = non_empty(&value),
+            TrackField::Artist => track.artist = non_empty(&value),
+            TrackField::Album => track.album = non_empty(&value),
+            _ => track.track_number = value.parse().ok(),
+        }
+        track.inferred.push(field);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    fn default_patterns() -> Vec<String> {
+        DEFAULT_PATH_PATTERNS
+            .iter()
+            .map(|pattern| pattern.to_string())
+            .collect()
+    }
+
+    #[wasm_bindgen_test]
+    fn infer_missing_fields() {
+        let mut track = Track {
+            location: Some("file:///music/Artist/Album/03%20-%20Title%20-%20Live.flac".to_string()),
+            album: Some("Tagged".to_string()),
+            ..Default::default()
+        };
+        let id = track.id();
+        infer_from_path(&mut track, &default_patterns());
+
+        assert_eq!(Some("Title - Live".to_string()), track.title);
+        assert_eq!(Some("Artist".to_string()), track.artist);
+        assert_eq!(Some("Tagged".to_string()), track.album);
+        assert_eq!(Some(3), track.track_number);
+        assert_eq!(
+            vec![
+                TrackField::Artist,
+                TrackField::TrackNumber,
+                TrackField::Title
+            ],
+            track.inferred
+        );
+        // inferred fields don't change the id
+        assert_eq!(id, track.id());
+
+        infer_from_path(&mut track, &["{artist} - {title}".to_string()]);
+        assert_eq!(Some("03".to_string()), track.artist);
+        assert_eq!(Some("Title - Live".to_string()), track.title);
+        assert_eq!(None, track.track_number);
+    }
+
+    #[wasm_bindgen_test]
+    fn windows_path() {
+        let mut track = Track {
+            location: Some(r"C:\Music\Artist - Title.mp3".to_string()),
+            ..Default::default()
+        };
+        infer_from_path(&mut track, &default_patterns());
+
+        assert_eq!(Some("Artist".to_string()), track.artist);
+        assert_eq!(Some("Title".to_string()), track.title);
+    }
+}
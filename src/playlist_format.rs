@@ -0,0 +1,268 @@
+use crate::csv_import::detect_delimiter;
+use crate::itunes::{parse_itunes_library, Library};
+use crate::jspf::parse_jspf;
+use crate::m3u::parse_m3u;
+use crate::parse_error::ParseError;
+use crate::playlist_types::*;
+use crate::pls::parse_pls;
+use crate::text_encoding::decode_text;
+use crate::wpl::{parse_asx, parse_wpl};
+use crate::xspf::parse_xspf;
+use strum_macros::{Display, EnumIter, EnumString};
+
+const SNIFF_LENGTH: usize = 4096;
+
+#[derive(Debug, Clone, Copy, PartialEq, Display, EnumIter, EnumString)]
+pub enum PlaylistFormat {
+    #[strum(serialize = "XSPF")]
+    Xspf,
+    #[strum(serialize = "JSPF")]
+    Jspf,
+    #[strum(serialize = "M3U/M3U8")]
+    M3u,
+    #[strum(serialize = "PLS")]
+    Pls,
+    #[strum(serialize = "WPL")]
+    Wpl,
+    #[strum(serialize = "ASX")]
+    Asx,
+    #[strum(serialize = "iTunes library")]
+    ItunesLibrary,
+    #[strum(serialize = "CSV/TSV")]
+    Csv,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Detection {
+    pub format: PlaylistFormat,
+    pub ambiguous: bool,
+}
+
+pub enum ParsedInput {
+    Playlist(Box<Playlist>),
+    Library(Library),
+    Table(String),
+}
+
+impl PlaylistFormat {
+    fn from_file_name(file_name: &str) -> Option<PlaylistFormat> {
+        let file_name = file_name.to_lowercase();
+        match file_name.rsplit('.').next().unwrap_or_default() {
+            "xspf" => Some(PlaylistFormat::Xspf),
+            "jspf" | "json" => Some(PlaylistFormat::Jspf),
+            "m3u" | "m3u8" => Some(PlaylistFormat::M3u),
+            "pls" => Some(PlaylistFormat::Pls),
+            "wpl" => Some(PlaylistFormat::Wpl),
+            "asx" | "wax" | "wvx" => Some(PlaylistFormat::Asx),
+            "xml" => Some(PlaylistFormat::ItunesLibrary),
+            "csv" | "tsv" => Some(PlaylistFormat::Csv),
+            _ => None,
+        }
+    }
+}
+
+// the cut can split a UTF-8 character, which would make the whole beginning look like
+// Windows-1252, so an incomplete character at the end is left out
+fn sniff_text(content: &[u8]) -> String {
+    let sniffed = &content[..content.len().min(SNIFF_LENGTH)];
+    match std::str::from_utf8(sniffed) {
+        Err(error) if error.error_len().is_none() && sniffed.len() < content.len() => {
+            decode_text(&sniffed[..error.valid_up_to()])
+        }
+        _ => decode_text(sniffed),
+    }
+}
+
+pub fn detect_format(file_name: &str, content: &[u8]) -> Detection {
+    let by_name = PlaylistFormat::from_file_name(file_name);
+    let text = sniff_text(content);
+
+    match (sniff_format(&text), by_name) {
+        (Sniffed::Certain(format), _) => Detection {
+            format,
+            ambiguous: false,
+        },
+        (Sniffed::Likely(format), by_name) => Detection {
+            format: by_name.unwrap_or(format),
+            ambiguous: by_name != Some(format),
+        },
+        (Sniffed::Unknown, by_name) => Detection {
+            format: by_name.unwrap_or(PlaylistFormat::Xspf),
+            ambiguous: true,
+        },
+    }
+}
+
+enum Sniffed {
+    Certain(PlaylistFormat),
+    Likely(PlaylistFormat),
+    Unknown,
+}
+
+fn sniff_format(text: &str) -> Sniffed {
+    let text = text.trim_start();
+
+    if text.starts_with("#EXTM3U") {
+        return Sniffed::Certain(PlaylistFormat::M3u);
+    }
+    if text
+        .get(..10)
+        .is_some_and(|start| start.eq_ignore_ascii_case("[playlist]"))
+    {
+        return Sniffed::Certain(PlaylistFormat::Pls);
+    }
+    if text.starts_with('{') {
+        return if text.contains("\"playlist\"") {
+            Sniffed::Certain(PlaylistFormat::Jspf)
+        } else {
+            Sniffed::Likely(PlaylistFormat::Jspf)
+        };
+    }
+    if text.starts_with('<') {
+        return sniff_xml(text);
+    }
+
+    let lines = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .take(10)
+        .collect::<Vec<_>>();
+    if lines.is_empty() {
+        return Sniffed::Unknown;
+    }
+    let looks_like_path = |line: &&str| {
+        line.starts_with('#') || line.contains("://") || line.contains('/') || line.contains('\\')
+    };
+    if lines.iter().all(looks_like_path) {
+        return Sniffed::Likely(PlaylistFormat::M3u);
+    }
+    let delimiter = char::from(detect_delimiter(text));
+    if lines.iter().all(|line| line.contains(delimiter)) {
+        return Sniffed::Likely(PlaylistFormat::Csv);
+    }
+
+    Sniffed::Unknown
+}
+
+fn sniff_xml(text: &str) -> Sniffed {
+    if text.contains("<?wpl") {
+        return Sniffed::Certain(PlaylistFormat::Wpl);
+    }
+    if text.contains("<!DOCTYPE plist") {
+        return Sniffed::Certain(PlaylistFormat::ItunesLibrary);
+    }
+
+    // skip the XML declaration, comments and doctype to find the root element
+    let root_element = text
+        .split('<')
+        .skip(1)
+        .find(|tag| tag.starts_with(|c: char| c.is_ascii_alphabetic()))
+        .map(|tag| {
+            let name = tag
+                .split(|c: char| c.is_whitespace() || c == '>' || c == '/')
+                .next()
+                .unwrap_or_default();
+            // ignore namespace prefixes
+            name.rsplit(':').next().unwrap_or_default().to_lowercase()
+        });
+
+    match root_element.as_deref() {
+        Some("playlist") => Sniffed::Certain(PlaylistFormat::Xspf),
+        Some("smil") => Sniffed::Certain(PlaylistFormat::Wpl),
+        Some("asx") => Sniffed::Certain(PlaylistFormat::Asx),
+        Some("plist") => Sniffed::Certain(PlaylistFormat::ItunesLibrary),
+        _ => Sniffed::Likely(PlaylistFormat::Xspf),
+    }
+}
+
+pub fn parse_input(
+    format: PlaylistFormat,
+    content: &[u8],
+) -> Result<(ParsedInput, Vec<ParseError>), ParseError> {
+    let parsed_input = match format {
+        PlaylistFormat::Xspf => {
+            let (playlist, skipped) = parse_xspf(content)?;
+            return Ok((ParsedInput::Playlist(Box::new(playlist)), skipped));
+        }
+        PlaylistFormat::Jspf => {
+            let (playlist, skipped) = parse_jspf(content)?;
+            return Ok((ParsedInput::Playlist(Box::new(playlist)), skipped));
+        }
+        PlaylistFormat::M3u => ParsedInput::Playlist(Box::new(parse_m3u(&decode_text(content)))),
+        PlaylistFormat::Pls => ParsedInput::Playlist(Box::new(parse_pls(&decode_text(content)))),
+        PlaylistFormat::Wpl => ParsedInput::Playlist(Box::new(parse_wpl(&decode_text(content))?)),
+        PlaylistFormat::Asx => ParsedInput::Playlist(Box::new(parse_asx(&decode_text(content))?)),
+        PlaylistFormat::ItunesLibrary => ParsedInput::Library(parse_itunes_library(content)?),
+        PlaylistFormat::Csv => ParsedInput::Table(decode_text(content)),
+    };
+    Ok((parsed_input, Vec::new()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    fn detect(file_name: &str, content: &str) -> Detection {
+        detect_format(file_name, content.as_bytes())
+    }
+
+    #[wasm_bindgen_test]
+    fn certain_formats() {
+        let certain = |format| Detection {
+            format,
+            ambiguous: false,
+        };
+
+        assert_eq!(
+            certain(PlaylistFormat::Xspf),
+            detect(
+                "list.xml",
+                "<?xml version=\"1.0\"?>\n<!-- c -->\n<playlist version=\"1\" xmlns=\"http://xspf.org/ns/0/\">"
+            )
+        );
+        assert_eq!(
+            certain(PlaylistFormat::M3u),
+            detect("list.txt", "#EXTM3U\n#EXTINF:1,a\na.mp3")
+        );
+        assert_eq!(
+            certain(PlaylistFormat::Pls),
+            detect("list", "[Playlist]\nFile1=a.mp3")
+        );
+        assert_eq!(
+            certain(PlaylistFormat::Jspf),
+            detect("list", "{\"playlist\": {}}")
+        );
+        assert_eq!(
+            certain(PlaylistFormat::ItunesLibrary),
+            detect("Library.xml", "<?xml version=\"1.0\"?>\n<!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"\">\n<plist>")
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn ambiguous_formats() {
+        assert_eq!(
+            Detection {
+                format: PlaylistFormat::Csv,
+                ambiguous: false,
+            },
+            detect("list.csv", "Title,Artist\nA,B\n")
+        );
+        assert_eq!(
+            Detection {
+                format: PlaylistFormat::M3u,
+                ambiguous: true,
+            },
+            detect("list", "/music/a.mp3\n/music/b.mp3\n")
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn split_character() {
+        let content = format!("{}ßß", "a".repeat(SNIFF_LENGTH - 3));
+        let text = sniff_text(content.as_bytes());
+        assert_eq!(SNIFF_LENGTH - 1, text.len());
+        assert!(text.ends_with('ß'));
+    }
+}
@@ -1,14 +1,68 @@
+use crate::artists::{artist_set_similarity, split_artists, split_featured};
+use crate::csv_import::write_csv;
+use crate::json_export::write_json;
+use crate::jspf::write_jspf;
+use crate::m3u::write_m3u8;
+use crate::matcher::{FieldWeights, MatchSettings, Matcher};
+use crate::normalize::{fold, fold_for_matching};
+use crate::spotify_types::spotify_track_uri;
+use crate::xspf::write_xspf;
+use strum_macros::{Display, EnumIter, EnumString};
+
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 
+const VERSION_KEYWORDS: [&str; 7] = [
+    "live",
+    "remix",
+    "karaoke",
+    "instrumental",
+    "acoustic",
+    "demo",
+    "cover",
+];
+const VERSION_PENALTY: f64 = 0.1;
+
 #[derive(Debug, Clone, PartialEq, Deserialize, Default)]
 pub struct Playlist {
     pub title: Option<String>,
+    pub creator: Option<String>,
     pub annotation: Option<String>,
+    pub info: Option<String>,
+    pub location: Option<String>,
+    pub identifier: Option<String>,
+    pub image: Option<String>,
+    pub date: Option<String>,
+    pub license: Option<String>,
+    #[serde(skip)]
+    pub links: Vec<XspfLink>,
+    #[serde(skip)]
+    pub meta: Vec<XspfMeta>,
+    #[serde(skip)]
+    pub extensions: Vec<XspfExtension>,
     #[serde(rename = "trackList")]
     pub track_list: TrackList,
 }
 
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct XspfLink {
+    pub rel: String,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct XspfMeta {
+    pub rel: String,
+    pub content: String,
+}
+
+// the content is kept as raw XML, since only the application understands it
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct XspfExtension {
+    pub application: String,
+    pub content: String,
+}
+
 impl Playlist {
     pub fn with_tracks_and_title(tracks: Vec<Track>, title: String) -> Playlist {
         Playlist {
@@ -18,22 +72,48 @@ impl Playlist {
         }
     }
 
+    pub fn export(&self, format: ExportFormat) -> String {
+        match format {
+            ExportFormat::Xspf => self.to_xspf(),
+            ExportFormat::Jspf => self.to_jspf(),
+            ExportFormat::M3u8 => write_m3u8(self),
+            ExportFormat::Csv => write_csv(self),
+            ExportFormat::Json => write_json(self),
+        }
+    }
+
+    pub fn to_jspf(&self) -> String {
+        write_jspf(self)
+    }
+
     pub fn to_xspf(&self) -> String {
-        let tracks = self
-            .track_list
-            .tracks
-            .iter()
-            .map(Track::to_xspf)
-            .collect::<Vec<_>>();
-        format!(
-            r#"<?xml version="1.0" encoding="UTF-8"?>
-<playlist version="1" xmlns="http://xspf.org/ns/0/">
-  <trackList>
-{}
-  </trackList>
-</playlist>"#,
-            tracks.join("\n")
-        )
+        write_xspf(self)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Display, EnumIter, EnumString)]
+pub enum ExportFormat {
+    #[strum(serialize = "XSPF")]
+    Xspf,
+    #[strum(serialize = "JSPF")]
+    Jspf,
+    #[strum(serialize = "M3U8")]
+    M3u8,
+    #[strum(serialize = "CSV")]
+    Csv,
+    #[strum(serialize = "JSON")]
+    Json,
+}
+
+impl ExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Xspf => "xspf",
+            ExportFormat::Jspf => "jspf",
+            ExportFormat::M3u8 => "m3u8",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+        }
     }
 }
 
@@ -43,7 +123,7 @@ pub struct TrackList {
     pub tracks: Vec<Track>,
 }
 
-#[derive(Debug, Clone, PartialEq, Deserialize, Hash, Default)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Default)]
 pub struct Track {
     pub location: Option<String>,
     pub identifier: Option<String>,
@@ -52,10 +132,83 @@ pub struct Track {
     pub artist: Option<String>,
     pub annotation: Option<String>,
     pub info: Option<String>,
+    pub image: Option<String>,
     pub album: Option<String>,
     #[serde(rename = "trackNum")]
     pub track_number: Option<i32>,
     pub duration: Option<i32>,
+    #[serde(skip)]
+    pub links: Vec<XspfLink>,
+    #[serde(skip)]
+    pub meta: Vec<XspfMeta>,
+    #[serde(skip)]
+    pub extensions: Vec<XspfExtension>,
+    #[serde(skip)]
+    pub isrc: Option<String>,
+    #[serde(skip)]
+    pub musicbrainz_id: Option<String>,
+    #[serde(skip)]
+    pub year: Option<i32>,
+    // the separate artists of Spotify tracks, `artist` joins them for display
+    #[serde(skip)]
+    pub artists: Vec<String>,
+    // fields that were derived from the location
+    #[serde(skip)]
+    pub inferred: Vec<TrackField>,
+    // names of the input playlists that contain the track
+    #[serde(skip)]
+    pub sources: Vec<String>,
+}
+
+// only the XSPF fields identify a track, so that stored id mappings stay valid
+impl Hash for Track {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let original = |field| !self.inferred.contains(&field);
+        self.location.hash(state);
+        self.identifier.hash(state);
+        self.title
+            .as_ref()
+            .filter(|_| original(TrackField::Title))
+            .hash(state);
+        self.artist
+            .as_ref()
+            .filter(|_| original(TrackField::Artist))
+            .hash(state);
+        self.annotation.hash(state);
+        self.info.hash(state);
+        self.album
+            .as_ref()
+            .filter(|_| original(TrackField::Album))
+            .hash(state);
+        self.track_number
+            .filter(|_| original(TrackField::TrackNumber))
+            .hash(state);
+        self.duration.hash(state);
+        // only hashed when present so that the ids of tracks without an ISRC stay the same
+        if let Some(isrc) = &self.isrc {
+            isrc.hash(state);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display, EnumIter, EnumString)]
+pub enum TrackField {
+    #[strum(serialize = "Title")]
+    Title,
+    #[strum(serialize = "Artist")]
+    Artist,
+    #[strum(serialize = "Album")]
+    Album,
+    #[strum(serialize = "Duration")]
+    Duration,
+    #[strum(serialize = "Track number")]
+    TrackNumber,
+    #[strum(serialize = "Identifier")]
+    Identifier,
+    #[strum(serialize = "ISRC")]
+    Isrc,
+    #[strum(serialize = "Location")]
+    Location,
 }
 
 impl Track {
@@ -67,13 +220,52 @@ impl Track {
         })
     }
 
+    // fills the typed fields from well-known identifiers and meta elements
+    pub fn read_known_meta(&mut self) {
+        for meta in self.meta.iter() {
+            // only the last part of the rel URI is compared, e.g. `http://example.com/ns/isrc`
+            let key = meta
+                .rel
+                .rsplit(['/', '#'])
+                .next()
+                .unwrap_or_default()
+                .chars()
+                .filter(char::is_ascii_alphanumeric)
+                .collect::<String>()
+                .to_lowercase();
+            let content = meta.content.trim();
+            match key.as_str() {
+                "isrc" => {
+                    self.isrc.get_or_insert_with(|| content.to_uppercase());
+                }
+                "mbid" | "recordingid" | "musicbrainzrecordingid" | "musicbrainztrackid" => {
+                    self.musicbrainz_id
+                        .get_or_insert_with(|| content.to_lowercase());
+                }
+                "year" | "date" | "releasedate" | "originaldate" if self.year.is_none() => {
+                    self.year = content.get(..4).and_then(|year| year.parse().ok());
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(identifier) = self.identifier.as_deref() {
+            if let Some(isrc) = identifier.strip_prefix("isrc:") {
+                self.isrc.get_or_insert_with(|| isrc.to_uppercase());
+            } else if let Some((_, mbid)) = identifier.split_once("musicbrainz.org/recording/") {
+                self.musicbrainz_id
+                    .get_or_insert_with(|| mbid.trim_end_matches('/').to_lowercase());
+            }
+        }
+    }
+
     pub fn query(&self) -> String {
         let whitespaces = js_sys::RegExp::new(r"\s+", "");
         js_sys::JsString::from(
             format!(
                 "{} {}",
-                self.artist.as_deref().unwrap_or_default(),
-                self.title.as_deref().unwrap_or_default()
+                fold(self.artist.as_deref().unwrap_or_default()),
+                fold(self.title.as_deref().unwrap_or_default())
             )
             .trim()
             .to_owned(),
@@ -85,11 +277,11 @@ impl Track {
     pub fn adjusted_query(&self) -> String {
         let brackets = js_sys::RegExp::new(r"[\(\[].*[\)\]]", "");
         let artist = String::from(
-            js_sys::JsString::from(self.artist.as_deref().unwrap_or_default())
+            js_sys::JsString::from(fold(self.artist.as_deref().unwrap_or_default()).as_str())
                 .replace_by_pattern(&brackets, ""),
         );
         let title = String::from(
-            js_sys::JsString::from(self.title.as_deref().unwrap_or_default())
+            js_sys::JsString::from(fold(self.title.as_deref().unwrap_or_default()).as_str())
                 .replace_by_pattern(&brackets, ""),
         );
 
@@ -99,60 +291,219 @@ impl Track {
             .into()
     }
 
-    pub fn similarity(&self, other: &Track) -> f64 {
-        let artist_a = self.artist.as_deref().unwrap_or_default().to_lowercase();
-        let artist_b = other.artist.as_deref().unwrap_or_default().to_lowercase();
-
-        let album_a = self.album.as_deref().unwrap_or_default().to_lowercase();
-        let album_b = other.album.as_deref().unwrap_or_default().to_lowercase();
+    // tracks that were taken from Spotify don't have to be searched
+    pub fn spotify_uri(&self) -> Option<String> {
+        self.identifier
+            .as_deref()
+            .and_then(spotify_track_uri)
+            .or_else(|| self.location.as_deref().and_then(spotify_track_uri))
+    }
 
-        let title_a = self.title.as_deref().unwrap_or_default().to_lowercase();
-        let title_b = other.title.as_deref().unwrap_or_default().to_lowercase();
+    // all credited artists, including the ones featured in the title
+    pub fn artist_set(&self) -> Vec<String> {
+        let mut artists = if self.artists.is_empty() {
+            split_artists(self.artist.as_deref().unwrap_or_default())
+        } else {
+            self.artists
+                .iter()
+                .map(|artist| fold_for_matching(artist))
+                .collect()
+        };
+        let (_, featured) = split_featured(self.title.as_deref().unwrap_or_default());
+        for artist in featured {
+            if !artists.contains(&artist) {
+                artists.push(artist);
+            }
+        }
+        artists
+    }
 
-        let duration_a = self.duration.unwrap_or_default();
-        let duration_b = other.duration.unwrap_or_default();
+    pub fn similarity(&self, other: &Track, settings: &MatchSettings) -> Similarity {
+        let mut similarity = self.compare_fields(other, settings.algorithm.matcher());
+        similarity.penalties = self.version_penalties(other);
+        similarity.weights = settings.weights;
+        similarity.exact = self.exact_match(other);
+        similarity.score = if similarity.exact.is_some() {
+            1.0
+        } else {
+            similarity.weighted_score()
+        };
+        similarity
+    }
 
-        let duration_similarity = (1.0
-            - f64::from(2 * (duration_a - duration_b).abs()) / f64::from(duration_a + duration_b))
-        .powi(2);
+    // the same ISRC or MusicBrainz recording is the same recording, no matter how it is spelled
+    pub fn exact_match(&self, other: &Track) -> Option<ExactMatch> {
+        let same = |a: &Option<String>, b: &Option<String>| matches!((a, b), (Some(a), Some(b)) if a.eq_ignore_ascii_case(b));
+        if same(&self.isrc, &other.isrc) {
+            Some(ExactMatch::Isrc)
+        } else if same(&self.musicbrainz_id, &other.musicbrainz_id) {
+            Some(ExactMatch::MusicBrainz)
+        } else {
+            None
+        }
+    }
 
-        (strsim::jaro(&artist_a, &artist_b) * 2.0
-            + strsim::jaro(&album_a, &album_b)
-            + strsim::jaro(&title_a, &title_b) * 2.0
-            + duration_similarity * 5.0)
-            / 10.0
+    // a live recording or a remix is a different song, even if the title is the same otherwise
+    fn version_penalties(&self, other: &Track) -> Vec<Penalty> {
+        let words = |track: &Track| {
+            fold_for_matching(track.title.as_deref().unwrap_or_default())
+                .split(' ')
+                .map(str::to_owned)
+                .collect::<Vec<_>>()
+        };
+        let (words_a, words_b) = (words(self), words(other));
+        VERSION_KEYWORDS
+            .iter()
+            .filter(|keyword| {
+                words_a.iter().any(|word| word == *keyword)
+                    != words_b.iter().any(|word| word == *keyword)
+            })
+            .map(|keyword| Penalty {
+                reason: format!("only one is a {} version", keyword),
+                value: VERSION_PENALTY,
+            })
+            .collect()
     }
 
-    pub fn to_xspf(&self) -> String {
-        let mut elements = Vec::with_capacity(9);
-        if let Some(location) = self.location.as_ref() {
-            elements.push(format!("      <location>{}</location>", location));
-        }
-        if let Some(identifier) = self.identifier.as_ref() {
-            elements.push(format!("      <identifier>{}</identifier>", identifier));
-        }
-        if let Some(title) = self.title.as_ref() {
-            elements.push(format!("      <title>{}</title>", title));
-        }
-        if let Some(artist) = self.artist.as_ref() {
-            elements.push(format!("      <creator>{}</creator>", artist));
-        }
-        if let Some(annotation) = self.annotation.as_ref() {
-            elements.push(format!("      <annotation>{}</annotation>", annotation));
-        }
-        if let Some(info) = self.info.as_ref() {
-            elements.push(format!("      <info>{}</info>", info));
-        }
-        if let Some(album) = self.album.as_ref() {
-            elements.push(format!("      <album>{}</album>", album));
-        }
-        if let Some(track_number) = self.track_number {
-            elements.push(format!("      <trackNum>{}</trackNum>", track_number));
+    fn compare_fields(&self, other: &Track, matcher: &dyn Matcher) -> Similarity {
+        // fields that are missing from either track can't tell whether the tracks are the same
+        let compare = |a: &str, b: &str| {
+            Some(matcher.similarity(a, b)).filter(|_| !a.is_empty() && !b.is_empty())
+        };
+
+        let artist_a = fold_for_matching(self.artist.as_deref().unwrap_or_default());
+        let artist_b = fold_for_matching(other.artist.as_deref().unwrap_or_default());
+
+        let album_a = fold_for_matching(self.album.as_deref().unwrap_or_default());
+        let album_b = fold_for_matching(other.album.as_deref().unwrap_or_default());
+
+        let title_a = fold_for_matching(self.title.as_deref().unwrap_or_default());
+        let title_b = fold_for_matching(other.title.as_deref().unwrap_or_default());
+
+        let duration = match (self.duration, other.duration) {
+            (Some(duration_a), Some(duration_b)) if duration_a > 0 && duration_b > 0 => {
+                let (duration_a, duration_b) = (f64::from(duration_a), f64::from(duration_b));
+                Some(
+                    (1.0 - 2.0 * (duration_a - duration_b).abs() / (duration_a + duration_b))
+                        .max(0.0)
+                        .powi(2),
+                )
+            }
+            _ => None,
+        };
+
+        // featured artists may be credited in the title of one track and as artist of the other
+        let (bare_title_a, _) = split_featured(self.title.as_deref().unwrap_or_default());
+        let (bare_title_b, _) = split_featured(other.title.as_deref().unwrap_or_default());
+
+        Similarity {
+            title: compare(&title_a, &title_b)
+                .map(|similarity| similarity.max(matcher.similarity(&bare_title_a, &bare_title_b))),
+            artist: compare(&artist_a, &artist_b).map(|similarity| {
+                similarity.max(artist_set_similarity(
+                    &self.artist_set(),
+                    &other.artist_set(),
+                    matcher,
+                ))
+            }),
+            album: compare(&album_a, &album_b),
+            duration,
+            track_number: match (self.track_number, other.track_number) {
+                (Some(number_a), Some(number_b)) if number_a > 0 && number_b > 0 => {
+                    Some(if number_a == number_b { 1.0 } else { 0.0 })
+                }
+                _ => None,
+            },
+            ..Default::default()
         }
-        if let Some(duration) = self.duration {
-            elements.push(format!("      <duration>{}</duration>", duration));
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Penalty {
+    pub reason: String,
+    pub value: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Similarity {
+    // `None` if the field is missing from either track
+    pub title: Option<f64>,
+    pub artist: Option<f64>,
+    pub album: Option<f64>,
+    pub duration: Option<f64>,
+    pub track_number: Option<f64>,
+    pub penalties: Vec<Penalty>,
+    pub weights: FieldWeights,
+    // both tracks are known to be the same recording
+    pub exact: Option<ExactMatch>,
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Display)]
+pub enum ExactMatch {
+    #[strum(serialize = "ISRC")]
+    Isrc,
+    #[strum(serialize = "MusicBrainz ID")]
+    MusicBrainz,
+}
+
+impl Similarity {
+    // exact matches come first, then the most similar tracks
+    pub fn sort_key(&self) -> (bool, isize) {
+        (self.exact.is_none(), -(self.score * 1_000.0) as isize)
+    }
+
+    // the fields that could be compared, with their similarity and weight
+    pub fn fields(&self) -> Vec<(TrackField, f64, f64)> {
+        vec![
+            (TrackField::Title, self.title, self.weights.title),
+            (TrackField::Artist, self.artist, self.weights.artist),
+            (TrackField::Album, self.album, self.weights.album),
+            (TrackField::Duration, self.duration, self.weights.duration),
+            (
+                TrackField::TrackNumber,
+                self.track_number,
+                self.weights.track_number,
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(field, similarity, weight)| Some((field, similarity?, weight)))
+        .filter(|(_, similarity, _)| similarity.is_finite())
+        .collect()
+    }
+
+    // the share of the score every field contributed, fields without weight don't count
+    pub fn contributions(&self) -> Vec<(TrackField, f64)> {
+        let fields = self
+            .fields()
+            .into_iter()
+            .filter(|(_, _, weight)| *weight > 0.0)
+            .collect::<Vec<_>>();
+        let weight_sum = fields.iter().map(|(_, _, weight)| weight).sum::<f64>();
+        fields
+            .into_iter()
+            .map(|(field, similarity, weight)| (field, similarity * weight / weight_sum))
+            .collect()
+    }
+
+    // the weights of the missing fields are left out, so the score stays between 0 and 1
+    fn weighted_score(&self) -> f64 {
+        let contributions = self.contributions();
+        if contributions.is_empty() {
+            return 0.0;
         }
-        format!("    <track>\n{}\n    </track>", elements.join("\n"))
+        let penalty = self
+            .penalties
+            .iter()
+            .map(|penalty| penalty.value)
+            .sum::<f64>();
+        (contributions
+            .iter()
+            .map(|(_, contribution)| contribution)
+            .sum::<f64>()
+            - penalty)
+            .clamp(0.0, 1.0)
     }
 }
 
@@ -182,4 +533,175 @@ mod tests {
 
         assert_eq!("Artist Title", track.adjusted_query());
     }
+
+    #[wasm_bindgen_test]
+    fn normalized_query() {
+        let track = Track {
+            artist: Some("Beyoncé".to_string()),
+            title: Some("Ｈａｌｏ ’Live’".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!("Beyonce Halo 'Live'", track.query());
+    }
+
+    #[wasm_bindgen_test]
+    fn normalized_similarity() {
+        let track = Track {
+            artist: Some("Beyoncé & Jay-Z".to_string()),
+            title: Some("Don’t Hurt Yourself".to_string()),
+            album: Some("Lemonade".to_string()),
+            duration: Some(233_000),
+            ..Default::default()
+        };
+        let other = Track {
+            artist: Some("BEYONCE and JAY-Z".to_string()),
+            title: Some("Don't Hurt Yourself".to_string()),
+            ..track.clone()
+        };
+
+        assert!((track.similarity(&other, &MatchSettings::default()).score - 1.0).abs() < 1e-9);
+    }
+
+    #[wasm_bindgen_test]
+    fn missing_fields() {
+        let track = Track {
+            artist: Some("Artist".to_string()),
+            title: Some("Title".to_string()),
+            ..Default::default()
+        };
+        let other = Track {
+            album: Some("Album".to_string()),
+            duration: Some(200_000),
+            ..track.clone()
+        };
+
+        let similarity = track.similarity(&other, &MatchSettings::default());
+        assert_eq!(None, similarity.album);
+        assert_eq!(None, similarity.duration);
+        assert_eq!(
+            vec![TrackField::Title, TrackField::Artist],
+            similarity
+                .contributions()
+                .into_iter()
+                .map(|(field, _)| field)
+                .collect::<Vec<_>>()
+        );
+        assert!((similarity.score - 1.0).abs() < 1e-9);
+
+        let nothing = Track::default().similarity(&Track::default(), &MatchSettings::default());
+        assert_eq!(0.0, nothing.score);
+
+        // very different durations don't wrap around to a high similarity
+        let short = Track {
+            duration: Some(10_000),
+            ..other.clone()
+        };
+        let similarity = short.similarity(&other, &MatchSettings::default());
+        assert_eq!(Some(0.0), similarity.duration);
+    }
+
+    #[wasm_bindgen_test]
+    fn isrc_match() {
+        let track = Track {
+            artist: Some("Artist".to_string()),
+            title: Some("Title".to_string()),
+            isrc: Some("USRC17607839".to_string()),
+            ..Default::default()
+        };
+        let recording = Track {
+            title: Some("Completely Different (Remastered)".to_string()),
+            isrc: Some("usrc17607839".to_string()),
+            ..track.clone()
+        };
+        let namesake = Track {
+            isrc: Some("GBAYE0601498".to_string()),
+            ..track.clone()
+        };
+
+        let exact = track.similarity(&recording, &MatchSettings::default());
+        let similar = track.similarity(&namesake, &MatchSettings::default());
+        assert_eq!(Some(ExactMatch::Isrc), exact.exact);
+        assert_eq!(None, similar.exact);
+        assert_eq!(1.0, exact.score);
+        assert!(exact.sort_key() < similar.sort_key());
+    }
+
+    #[wasm_bindgen_test]
+    fn musicbrainz_match() {
+        let mbid = "b1a9c0e9-d987-4042-ae91-78d6a3267d69";
+        let track = Track {
+            title: Some("Title".to_string()),
+            musicbrainz_id: Some(mbid.to_string()),
+            ..Default::default()
+        };
+        let recording = Track {
+            title: Some("Other".to_string()),
+            musicbrainz_id: Some(mbid.to_uppercase()),
+            ..Default::default()
+        };
+
+        let similarity = track.similarity(&recording, &MatchSettings::default());
+        assert_eq!(Some(ExactMatch::MusicBrainz), similarity.exact);
+        assert_eq!(1.0, similarity.score);
+        assert_eq!(None, track.exact_match(&Track::default()));
+    }
+
+    #[wasm_bindgen_test]
+    fn version_penalty() {
+        let track = Track {
+            artist: Some("Artist".to_string()),
+            title: Some("Title".to_string()),
+            track_number: Some(3),
+            ..Default::default()
+        };
+        let live = Track {
+            title: Some("Title (Live)".to_string()),
+            track_number: Some(4),
+            ..track.clone()
+        };
+
+        let similarity = track.similarity(&live, &MatchSettings::default());
+        assert_eq!(1, similarity.penalties.len());
+        assert_eq!(Some(0.0), similarity.track_number);
+        let unpenalized = similarity
+            .contributions()
+            .iter()
+            .map(|(_, contribution)| contribution)
+            .sum::<f64>();
+        assert!((unpenalized - similarity.score - 0.1).abs() < 1e-9);
+        assert!(track
+            .similarity(&track, &MatchSettings::default())
+            .penalties
+            .is_empty());
+    }
+
+    #[wasm_bindgen_test]
+    fn read_known_meta() {
+        let mut track = Track {
+            identifier: Some(
+                "https://musicbrainz.org/recording/B1A9C0E9-D987-4042-AE91-78D6A3267D69"
+                    .to_string(),
+            ),
+            meta: vec![
+                XspfMeta {
+                    rel: "http://example.com/ns/ISRC".to_string(),
+                    content: "usrc17607839".to_string(),
+                },
+                XspfMeta {
+                    rel: "http://example.com/ns#release-date".to_string(),
+                    content: "1976-05-01".to_string(),
+                },
+            ],
+            ..Default::default()
+        };
+        track.read_known_meta();
+
+        assert_eq!(Some("USRC17607839".to_string()), track.isrc);
+        assert_eq!(
+            Some("b1a9c0e9-d987-4042-ae91-78d6a3267d69".to_string()),
+            track.musicbrainz_id
+        );
+        assert_eq!(Some(1976), track.year);
+    }
 }
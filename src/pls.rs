@@ -0,0 +1,105 @@
+use crate::m3u::{non_empty, split_display_name};
+use crate::playlist_types::*;
+
+use std::collections::BTreeMap;
+
+pub fn parse_pls(content: &str) -> Playlist {
+    let mut playlist = Playlist::default();
+    let mut entries = BTreeMap::<u32, Track>::new();
+
+    for line in content.lines().map(str::trim) {
+        let (key, value) = match line.split_once('=') {
+            Some((key, value)) => (key.trim().to_lowercase(), value.trim()),
+            None => continue,
+        };
+
+        if key == "x-title" || key == "playlistname" {
+            playlist.title = non_empty(value);
+            continue;
+        }
+
+        let field_end = key.find(|c: char| c.is_ascii_digit()).unwrap_or(key.len());
+        let (field, index) = key.split_at(field_end);
+        let index = match index.parse::<u32>() {
+            Ok(index) => index,
+            Err(_) => continue,
+        };
+        let track = entries.entry(index).or_default();
+
+        match field {
+            "file" => track.location = non_empty(value),
+            "title" => {
+                let (artist, title) = split_display_name(value);
+                track.artist = artist;
+                track.title = title;
+            }
+            "length" => {
+                track.duration = value
+                    .parse::<i32>()
+                    .ok()
+                    .filter(|seconds| *seconds > 0)
+                    .and_then(|seconds| seconds.checked_mul(1_000))
+            }
+            _ => {}
+        }
+    }
+
+    playlist.track_list.tracks = entries
+        .into_values()
+        .filter(|track| track.location.is_some())
+        .collect();
+
+    playlist
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn numbered_entries() {
+        let playlist = parse_pls(
+            "[playlist]\nX-Title=Mix\nFile2=http://radio/stream\nTitle2=Radio\nLength2=-1\nFile1=music/01.mp3\nTitle1=Artist - Title\nLength1=215\nNumberOfEntries=2\nVersion=2\n",
+        );
+
+        assert_eq!(Some("Mix".to_string()), playlist.title);
+        assert_eq!(
+            vec![
+                Track {
+                    location: Some("music/01.mp3".to_string()),
+                    artist: Some("Artist".to_string()),
+                    title: Some("Title".to_string()),
+                    duration: Some(215_000),
+                    ..Default::default()
+                },
+                Track {
+                    location: Some("http://radio/stream".to_string()),
+                    title: Some("Radio".to_string()),
+                    ..Default::default()
+                },
+            ],
+            playlist.track_list.tracks
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn entries_without_file() {
+        let playlist = parse_pls("[playlist]\nTitle1=Orphan\nFile2=song.mp3\n");
+
+        assert_eq!(
+            vec![Track {
+                location: Some("song.mp3".to_string()),
+                ..Default::default()
+            }],
+            playlist.track_list.tracks
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn overlong_length() {
+        let playlist = parse_pls("[playlist]\nFile1=stream.mp3\nLength1=2147484\n");
+
+        assert_eq!(None, playlist.track_list.tracks[0].duration);
+    }
+}
@@ -0,0 +1,138 @@
+use crate::normalize::fold;
+use crate::playlist_types::Track;
+
+// the field filters of the Spotify search
+#[derive(Debug, Clone, Default)]
+pub struct SearchQuery {
+    filters: Vec<String>,
+}
+
+impl SearchQuery {
+    // empty values are left out
+    pub fn filter(mut self, field: &str, value: Option<&str>) -> SearchQuery {
+        let value = fold(value.unwrap_or_default())
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        if !value.is_empty() {
+            self.filters.push(format!("{}:{}", field, quote(&value)));
+        }
+        self
+    }
+
+    pub fn year(mut self, year: Option<i32>) -> SearchQuery {
+        if let Some(year) = year.filter(|year| *year > 0) {
+            self.filters.push(format!("year:{}", year));
+        }
+        self
+    }
+
+    pub fn build(self) -> Option<String> {
+        Some(self.filters.join(" ")).filter(|query| !query.is_empty())
+    }
+}
+
+// values are quoted so they can contain spaces, quotes within them are escaped
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// the ISRC identifies the recording, it is only made of letters and digits
+pub fn isrc_query(track: &Track) -> Option<String> {
+    let isrc = track
+        .isrc
+        .as_deref()?
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .collect::<String>();
+    Some(format!("isrc:{}", isrc.to_uppercase())).filter(|_| !isrc.is_empty())
+}
+
+// without a title the filters would find any track of the artist or album
+pub fn field_qualified_query(track: &Track) -> Option<String> {
+    track
+        .title
+        .as_ref()
+        .filter(|title| !title.trim().is_empty())?;
+    SearchQuery::default()
+        .filter("track", track.title.as_deref())
+        .filter("artist", track.artist.as_deref())
+        .filter("album", track.album.as_deref())
+        .year(track.year)
+        .build()
+}
+
+// the queries that are tried one after another until the search finds a track,
+// the precise ones first and the bare ones as a fallback
+pub fn search_queries(track: &Track) -> Vec<String> {
+    let mut queries = Vec::new();
+    for query in isrc_query(track)
+        .into_iter()
+        .chain(field_qualified_query(track))
+        .chain(vec![track.query(), track.adjusted_query()])
+    {
+        if !query.is_empty() && !queries.contains(&query) {
+            queries.push(query);
+        }
+    }
+    queries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn qualified_query() {
+        let track = Track {
+            artist: Some("Artist".to_string()),
+            title: Some("Some  Title".to_string()),
+            album: Some("Album".to_string()),
+            year: Some(1976),
+            ..Default::default()
+        };
+        assert_eq!(
+            Some(r#"track:"Some Title" artist:"Artist" album:"Album" year:1976"#.to_string()),
+            field_qualified_query(&track)
+        );
+
+        let without_title = Track {
+            title: None,
+            ..track.clone()
+        };
+        assert_eq!(None, field_qualified_query(&without_title));
+
+        let title_only = Track {
+            title: Some("Title".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            Some(r#"track:"Title""#.to_string()),
+            field_qualified_query(&title_only)
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn isrc() {
+        let track = Track {
+            isrc: Some("us-rc1-76-07839".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(Some("isrc:USRC17607839".to_string()), isrc_query(&track));
+        assert_eq!(None, isrc_query(&Track::default()));
+    }
+
+    #[wasm_bindgen_test]
+    fn escaped_quotes() {
+        let track = Track {
+            artist: Some(r#"Weird "Al" Yankovic"#.to_string()),
+            title: Some(r#"Back\Slash"#.to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            Some(r#"track:"Back\\Slash" artist:"Weird \"Al\" Yankovic""#.to_string()),
+            field_qualified_query(&track)
+        );
+    }
+}
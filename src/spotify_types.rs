@@ -10,6 +10,11 @@ pub struct SpotifyAlbum {
     pub name: String,
 }
 
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SpotifyExternalIds {
+    pub isrc: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SpotifyTrack {
     pub uri: String,
@@ -18,23 +23,27 @@ pub struct SpotifyTrack {
     pub name: String,
     pub track_number: i32,
     pub duration_ms: i32,
+    // local files don't have any
+    #[serde(default)]
+    pub external_ids: SpotifyExternalIds,
 }
 
 impl From<SpotifyTrack> for Track {
     fn from(f: SpotifyTrack) -> Track {
+        let artists = f
+            .artists
+            .into_iter()
+            .map(|artist| artist.name)
+            .collect::<Vec<_>>();
         Track {
             identifier: Some(f.uri),
             title: Some(f.name),
             track_number: Some(f.track_number),
             duration: Some(f.duration_ms),
-            artist: Some(
-                f.artists
-                    .into_iter()
-                    .map(|artist| artist.name)
-                    .collect::<Vec<_>>()
-                    .join(", "),
-            ),
+            artist: Some(artists.join(", ")),
+            artists,
             album: Some(f.album.name),
+            isrc: f.external_ids.isrc.map(|isrc| isrc.to_uppercase()),
             ..Default::default()
         }
     }
@@ -42,7 +51,7 @@ impl From<SpotifyTrack> for Track {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SpotifyTracks {
-    pub tracks: Vec<SpotifyTrack>,
+    pub tracks: Vec<Option<SpotifyTrack>>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -66,6 +75,13 @@ pub struct SpotifyUserProfile {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SpotifyPagination<T> {
     pub items: Vec<T>,
+    pub next: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SpotifyPlaylistItem {
+    // removed tracks are null and podcast episodes don't have the fields of a track
+    pub track: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -78,3 +94,79 @@ pub struct SpotifyCreatePlaylist {
     pub name: String,
     pub public: bool,
 }
+
+// the URI of a track given as `spotify:track:...` or as an open.spotify.com link, e.g.
+// `https://open.spotify.com/intl-de/track/6rqhFgbbKwnb9MLmUQDhG6?si=...`
+pub fn spotify_track_uri(value: &str) -> Option<String> {
+    let value = value.trim();
+    let id = if let Some(id) = value.strip_prefix("spotify:track:") {
+        id
+    } else {
+        let link = value
+            .strip_prefix("https://")
+            .or_else(|| value.strip_prefix("http://"))
+            .unwrap_or(value);
+        let path = link.strip_prefix("open.spotify.com/")?;
+        let mut segments = path
+            .split('/')
+            .skip_while(|segment| segment.starts_with("intl-"));
+        if segments.next() != Some("track") {
+            return None;
+        }
+        segments.next()?.split(['?', '#']).next()?
+    };
+    // Spotify ids are base62 encoded
+    if id.len() == 22 && id.chars().all(|c| c.is_ascii_alphanumeric()) {
+        Some(format!("spotify:track:{}", id))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn track_uri() {
+        let uri = Some("spotify:track:6rqhFgbbKwnb9MLmUQDhG6".to_string());
+        assert_eq!(
+            uri,
+            spotify_track_uri("spotify:track:6rqhFgbbKwnb9MLmUQDhG6")
+        );
+        assert_eq!(
+            uri,
+            spotify_track_uri("https://open.spotify.com/track/6rqhFgbbKwnb9MLmUQDhG6?si=abc")
+        );
+        assert_eq!(
+            uri,
+            spotify_track_uri("https://open.spotify.com/intl-de/track/6rqhFgbbKwnb9MLmUQDhG6")
+        );
+        assert_eq!(
+            None,
+            spotify_track_uri("https://open.spotify.com/album/6rqhFgbbKwnb9MLmUQDhG6")
+        );
+        assert_eq!(None, spotify_track_uri("spotify:track:invalid"));
+        assert_eq!(None, spotify_track_uri("file:///music/track.mp3"));
+    }
+
+    #[wasm_bindgen_test]
+    fn tracks_with_null() {
+        let tracks: SpotifyTracks = serde_json::from_str(
+            r#"{"tracks": [null, {
+                "uri": "spotify:track:6rqhFgbbKwnb9MLmUQDhG6",
+                "album": {"name": "Album"},
+                "artists": [{"name": "Artist"}],
+                "name": "Title",
+                "track_number": 1,
+                "duration_ms": 215000
+            }]}"#,
+        )
+        .unwrap();
+        assert_eq!(2, tracks.tracks.len());
+        assert!(tracks.tracks[0].is_none());
+        let track = Track::from(tracks.tracks.into_iter().flatten().next().unwrap());
+        assert_eq!("spotify:track:6rqhFgbbKwnb9MLmUQDhG6", track.id());
+    }
+}
@@ -0,0 +1,82 @@
+// text with a flag whether it differs from the other text
+pub type Segments = Vec<(String, bool)>;
+
+// splits both texts into segments, flagging the characters that are not part of their longest
+// common subsequence, case is ignored like in the similarity
+pub fn diff_chars(a: &str, b: &str) -> (Segments, Segments) {
+    let chars_a = a.chars().collect::<Vec<_>>();
+    let chars_b = b.chars().collect::<Vec<_>>();
+    let equal = |x: char, y: char| x.to_lowercase().eq(y.to_lowercase());
+
+    // lengths[i][j] is the length of the common subsequence of chars_a[i..] and chars_b[j..]
+    let mut lengths = vec![vec![0usize; chars_b.len() + 1]; chars_a.len() + 1];
+    for i in (0..chars_a.len()).rev() {
+        for j in (0..chars_b.len()).rev() {
+            lengths[i][j] = if equal(chars_a[i], chars_b[j]) {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut differs_a = vec![true; chars_a.len()];
+    let mut differs_b = vec![true; chars_b.len()];
+    let (mut i, mut j) = (0, 0);
+    while i < chars_a.len() && j < chars_b.len() {
+        if equal(chars_a[i], chars_b[j]) {
+            differs_a[i] = false;
+            differs_b[j] = false;
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+
+    (
+        segments(&chars_a, &differs_a),
+        segments(&chars_b, &differs_b),
+    )
+}
+
+fn segments(chars: &[char], differs: &[bool]) -> Segments {
+    let mut segments: Segments = Vec::new();
+    for (c, differs) in chars.iter().zip(differs) {
+        match segments.last_mut() {
+            Some((text, last_differs)) if last_differs == differs => text.push(*c),
+            _ => segments.push((c.to_string(), *differs)),
+        }
+    }
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn diff() {
+        let (a, b) = diff_chars("Halo (Live)", "halo");
+        assert_eq!(
+            vec![("Halo".to_string(), false), (" (Live)".to_string(), true)],
+            a
+        );
+        assert_eq!(vec![("halo".to_string(), false)], b);
+
+        let (a, b) = diff_chars("Colour", "Color");
+        assert_eq!(
+            vec![
+                ("Colo".to_string(), false),
+                ("u".to_string(), true),
+                ("r".to_string(), false)
+            ],
+            a
+        );
+        assert_eq!(vec![("Color".to_string(), false)], b);
+        assert_eq!((Vec::new(), Vec::new()), diff_chars("", ""));
+    }
+}
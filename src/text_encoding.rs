@@ -0,0 +1,36 @@
+// Windows-1252 code points for the bytes 0x80..=0x9F, everything else maps to Latin-1
+const WINDOWS_1252: [char; 32] = [
+    '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8D}', 'Ž', '\u{8F}',
+    '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9D}', 'ž', 'Ÿ',
+];
+
+pub fn decode_text(content: &[u8]) -> String {
+    if let Some(content) = content.strip_prefix(b"\xEF\xBB\xBF") {
+        return String::from_utf8_lossy(content).into_owned();
+    }
+    if let Some(content) = content.strip_prefix(b"\xFF\xFE") {
+        return decode_utf16(content, u16::from_le_bytes);
+    }
+    if let Some(content) = content.strip_prefix(b"\xFE\xFF") {
+        return decode_utf16(content, u16::from_be_bytes);
+    }
+
+    match std::str::from_utf8(content) {
+        Ok(text) => text.to_owned(),
+        Err(_) => content
+            .iter()
+            .map(|&byte| match byte {
+                0x80..=0x9F => WINDOWS_1252[usize::from(byte - 0x80)],
+                _ => char::from(byte),
+            })
+            .collect(),
+    }
+}
+
+fn decode_utf16(content: &[u8], from_bytes: fn([u8; 2]) -> u16) -> String {
+    let units = content
+        .chunks_exact(2)
+        .map(|chunk| from_bytes([chunk[0], chunk[1]]))
+        .collect::<Vec<_>>();
+    String::from_utf16_lossy(&units)
+}
@@ -1,4 +1,5 @@
 use crate::playlist_types::*;
+use crate::text_diff::{diff_chars, Segments};
 use yew::{html::Html, prelude::*, Properties};
 
 use std::rc::Rc;
@@ -6,19 +7,22 @@ use std::rc::Rc;
 pub struct TrackItem {
     link: ComponentLink<Self>,
     props: Props,
+    show_details: bool,
 }
 
 #[derive(Clone, PartialEq, Properties)]
 pub struct Props {
     pub in_track: Rc<Track>,
-    pub out_tracks: Rc<Vec<(f64, Track)>>,
+    pub out_tracks: Rc<Vec<(Similarity, Track)>>,
     pub output_id: Option<String>,
+    pub show_sources: bool,
     pub onmappingchange: Callback<(String, Option<String>)>,
     pub onquerytrack: Callback<(String, String)>,
 }
 
 pub enum Msg {
     OutTrackSelected(String),
+    ToggleDetails,
     Noop,
 }
 
@@ -27,7 +31,11 @@ impl Component for TrackItem {
     type Properties = Props;
 
     fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
-        TrackItem { link, props }
+        TrackItem {
+            link,
+            props,
+            show_details: false,
+        }
     }
 
     fn update(&mut self, msg: Self::Message) -> ShouldRender {
@@ -57,6 +65,7 @@ impl Component for TrackItem {
                         .emit((self.props.in_track.id(), Some(output_id)));
                 }
             }
+            Msg::ToggleDetails => self.show_details = !self.show_details,
             Msg::Noop => {}
         }
         true
@@ -101,17 +110,45 @@ impl Component for TrackItem {
                 {
                     for out_tracks.iter().map(|(similarity, out_track)| {
                         let value = out_track.id();
+                        // fields that are missing from either track don't count, fields with
+                        // a weight of 0 are still compared
+                        let compared = similarity
+                            .fields()
+                            .into_iter()
+                            .map(|(field, _, _)| field)
+                            .collect::<Vec<_>>();
+                        let missing = [TrackField::Title, TrackField::Artist, TrackField::Album, TrackField::Duration]
+                            .iter()
+                            .filter(|field| !compared.contains(field))
+                            .map(|field| field.to_string().to_lowercase())
+                            .collect::<Vec<_>>();
+                        let score = if let Some(exact) = similarity.exact {
+                            exact.to_string()
+                        } else if missing.is_empty() {
+                            format!("{} %", (similarity.score * 100.0).round())
+                        } else {
+                            format!("{} %, without {}", (similarity.score * 100.0).round(), missing.join(", "))
+                        };
                         let text = format!(
-                            "[{} %] {} - {} - {} ({})",
-                            (similarity * 100.0).round(),
+                            "[{}] {} - {} - {} ({})",
+                            score,
                             out_track.title.as_deref().unwrap_or_default(),
                             out_track.artist.as_deref().unwrap_or_default(),
                             out_track.album.as_deref().unwrap_or_default(),
                             format_duration(out_track.duration.unwrap_or_default()),
                         );
                         let selected = output_id.as_ref().map_or(false, |output_id| *output_id == out_track.id());
+                        let tooltip = similarity
+                            .contributions()
+                            .into_iter()
+                            .map(|(field, contribution)| format!("{}: +{} %", field, (contribution * 100.0).round()))
+                            .chain(similarity.penalties.iter().map(|penalty| {
+                                format!("{}: -{} %", penalty.reason, (penalty.value * 100.0).round())
+                            }))
+                            .collect::<Vec<_>>()
+                            .join("\n");
                         html! {
-                            <option value={value} selected={selected}>{text}</option>
+                            <option value={value} selected={selected} title={tooltip}>{text}</option>
                         }
                     })
                 }
@@ -119,15 +156,164 @@ impl Component for TrackItem {
             </select>
         };
 
+        let onclick_details = self.link.callback(|_| Msg::ToggleDetails);
+        let columns = if self.props.show_sources { 6 } else { 5 };
+
         html! {
+            <>
             <tr>
-                <td>{in_track.title.as_deref().unwrap_or_default()}</td>
-                <td>{in_track.artist.as_deref().unwrap_or_default()}</td>
-                <td>{in_track.album.as_deref().unwrap_or_default()}</td>
+                <td class=inferred_class(in_track, TrackField::Title)>{in_track.title.as_deref().unwrap_or_default()}</td>
+                <td class=inferred_class(in_track, TrackField::Artist)>{in_track.artist.as_deref().unwrap_or_default()}</td>
+                <td class=inferred_class(in_track, TrackField::Album)>{in_track.album.as_deref().unwrap_or_default()}</td>
                 <td class="right">{format_duration(in_track.duration.unwrap_or_default())}</td>
-                <td>{render_select}</td>
+                {
+                    if self.props.show_sources {
+                        html! {<td>{in_track.sources.join(", ")}</td>}
+                    } else {
+                        html! {}
+                    }
+                }
+                <td>
+                    {render_select}
+                    <button class="details-toggle" onclick=onclick_details disabled=out_tracks.is_empty()>
+                        {if self.show_details { "Hide details" } else { "Details" }}
+                    </button>
+                </td>
+            </tr>
+            {
+                if self.show_details && !out_tracks.is_empty() {
+                    html! {
+                        <tr class="details">
+                            <td colspan=columns>
+                                {for out_tracks.iter().map(|(similarity, out_track)| render_details(in_track, similarity, out_track))}
+                            </td>
+                        </tr>
+                    }
+                } else {
+                    html! {}
+                }
+            }
+            </>
+        }
+    }
+}
+
+// how every field of a candidate compares to the input track
+fn render_details(in_track: &Track, similarity: &Similarity, out_track: &Track) -> Html {
+    let contributions = similarity.contributions();
+    let render_field = |field: TrackField, value: Option<f64>, input: Html, candidate: Html| {
+        let percent = |value: f64| format!("{} %", (value * 100.0).round());
+        let contribution = contributions
+            .iter()
+            .find(|(contributed, _)| *contributed == field)
+            .map_or_else(
+                || "-".to_owned(),
+                |(_, contribution)| percent(*contribution),
+            );
+        html! {
+            <tr>
+                <th>{field.to_string()}</th>
+                <td>{input}</td>
+                <td>{candidate}</td>
+                <td class="right">{value.map_or_else(|| "missing".to_owned(), percent)}</td>
+                <td class="right">{contribution}</td>
             </tr>
         }
+    };
+    let render_text =
+        |field: TrackField, value: Option<f64>, a: &Option<String>, b: &Option<String>| {
+            let (segments_a, segments_b) = diff_chars(
+                a.as_deref().unwrap_or_default(),
+                b.as_deref().unwrap_or_default(),
+            );
+            render_field(
+                field,
+                value,
+                render_segments(segments_a),
+                render_segments(segments_b),
+            )
+        };
+    let render_number =
+        |field: TrackField, value: Option<f64>, a: Option<String>, b: Option<String>| {
+            render_field(
+                field,
+                value,
+                html! {a.unwrap_or_default()},
+                html! {b.unwrap_or_default()},
+            )
+        };
+
+    html! {
+        <table class="similarity">
+            <caption>
+                {format!(
+                    "{} - {} [{} %{}]",
+                    out_track.title.as_deref().unwrap_or_default(),
+                    out_track.artist.as_deref().unwrap_or_default(),
+                    (similarity.score * 100.0).round(),
+                    similarity
+                        .exact
+                        .map_or_else(String::new, |exact| format!(", same {}", exact))
+                )}
+            </caption>
+            <tr>
+                <th></th>
+                <th>{"Input"}</th>
+                <th>{"Candidate"}</th>
+                <th class="right">{"Similarity"}</th>
+                <th class="right">{"Contribution"}</th>
+            </tr>
+            {render_text(TrackField::Title, similarity.title, &in_track.title, &out_track.title)}
+            {render_text(TrackField::Artist, similarity.artist, &in_track.artist, &out_track.artist)}
+            {render_text(TrackField::Album, similarity.album, &in_track.album, &out_track.album)}
+            {render_number(
+                TrackField::Duration,
+                similarity.duration,
+                in_track.duration.map(format_duration),
+                out_track.duration.map(format_duration),
+            )}
+            {render_number(
+                TrackField::TrackNumber,
+                similarity.track_number,
+                in_track.track_number.map(|number| number.to_string()),
+                out_track.track_number.map(|number| number.to_string()),
+            )}
+            {
+                for similarity.penalties.iter().map(|penalty| html! {
+                    <tr class="penalty">
+                        <th>{"Penalty"}</th>
+                        <td colspan=3>{&penalty.reason}</td>
+                        <td class="right">{format!("-{} %", (penalty.value * 100.0).round())}</td>
+                    </tr>
+                })
+            }
+        </table>
+    }
+}
+
+// the characters that differ from the other text are highlighted
+fn render_segments(segments: Segments) -> Html {
+    html! {
+        <>
+            {
+                for segments.into_iter().map(|(text, differs)| {
+                    if differs {
+                        html! {<mark>{text}</mark>}
+                    } else {
+                        html! {text}
+                    }
+                })
+            }
+        </>
+    }
+}
+
+// marks the fields that were derived from the location
+fn inferred_class(track: &Track, field: TrackField) -> &'static str {
+    if track.inferred.contains(&field) {
+        "inferred"
+    } else {
+        ""
     }
 }
 
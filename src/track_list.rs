@@ -2,7 +2,7 @@ use crate::playlist_types::*;
 use crate::TrackItem;
 use yew::{html::Html, prelude::*, Properties};
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 
 pub struct TrackList {
@@ -12,7 +12,7 @@ pub struct TrackList {
 #[derive(Clone, PartialEq, Properties)]
 pub struct Props {
     pub in_tracks: Rc<Vec<Rc<Track>>>,
-    pub out_tracks: Rc<HashMap<String, Rc<Vec<(f64, Track)>>>>,
+    pub out_tracks: Rc<HashMap<String, Rc<Vec<(Similarity, Track)>>>>,
     pub id_mapping: Rc<HashMap<String, String>>,
     pub onmappingchange: Callback<(String, Option<String>)>,
     pub onquerytrack: Callback<(String, String)>,
@@ -43,6 +43,16 @@ impl Component for TrackList {
     }
 
     fn view(&self) -> Html {
+        // the source is only of interest when several playlists were merged
+        let show_sources = self
+            .props
+            .in_tracks
+            .iter()
+            .flat_map(|in_track| in_track.sources.iter())
+            .collect::<HashSet<_>>()
+            .len()
+            > 1;
+
         html! {
             <div>
                 <table>
@@ -52,6 +62,13 @@ impl Component for TrackList {
                             <th>{"Artist"}</th>
                             <th>{"Album"}</th>
                             <th class="right">{"Length"}</th>
+                            {
+                                if show_sources {
+                                    html! {<th>{"Source"}</th>}
+                                } else {
+                                    html! {}
+                                }
+                            }
                             <th>{"Spotify"}</th>
                         </tr>
                     </thead>
@@ -66,6 +83,7 @@ impl Component for TrackList {
                                         in_track=in_track
                                         out_tracks=out_tracks
                                         output_id=output_id
+                                        show_sources=show_sources
                                         onmappingchange=self.props.onmappingchange.clone()
                                         onquerytrack=self.props.onquerytrack.clone()
                                     />}
@@ -0,0 +1,204 @@
+use crate::m3u::non_empty;
+use crate::playlist_types::*;
+
+use xml::attribute::OwnedAttribute;
+use xml::reader::{EventReader, XmlEvent};
+
+pub fn parse_wpl(content: &str) -> Result<Playlist, xml::reader::Error> {
+    let mut playlist = Playlist::default();
+    let mut path = Vec::<String>::new();
+
+    for event in EventReader::from_str(content) {
+        match event? {
+            XmlEvent::StartElement {
+                name, attributes, ..
+            } => {
+                let name = name.local_name.to_lowercase();
+                if name == "media" {
+                    let track = Track {
+                        location: attribute(&attributes, "src"),
+                        title: attribute(&attributes, "trackTitle"),
+                        artist: attribute(&attributes, "trackArtist"),
+                        album: attribute(&attributes, "albumTitle"),
+                        duration: attribute(&attributes, "duration")
+                            .and_then(|duration| duration.parse().ok()),
+                        ..Default::default()
+                    };
+                    if track.location.is_some() {
+                        playlist.track_list.tracks.push(track);
+                    }
+                }
+                path.push(name);
+            }
+            XmlEvent::EndElement { .. } => {
+                path.pop();
+            }
+            XmlEvent::Characters(text) if path.join("/").ends_with("head/title") => {
+                playlist.title = non_empty(&text);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(playlist)
+}
+
+pub fn parse_asx(content: &str) -> Result<Playlist, xml::reader::Error> {
+    let mut playlist = Playlist::default();
+    let mut path = Vec::<String>::new();
+    let mut entry: Option<Track> = None;
+
+    for event in EventReader::from_str(content) {
+        match event? {
+            XmlEvent::StartElement {
+                name, attributes, ..
+            } => {
+                let name = name.local_name.to_lowercase();
+                match (entry.as_mut(), name.as_str()) {
+                    (None, "entry") => entry = Some(Track::default()),
+                    (Some(track), "ref") if track.location.is_none() => {
+                        track.location = attribute(&attributes, "href");
+                    }
+                    (Some(track), "duration") => {
+                        track.duration =
+                            attribute(&attributes, "value").and_then(|value| parse_clock(&value));
+                    }
+                    (Some(track), "param")
+                        if attribute(&attributes, "name")
+                            .is_some_and(|name| name.eq_ignore_ascii_case("album")) =>
+                    {
+                        track.album = attribute(&attributes, "value");
+                    }
+                    _ => {}
+                }
+                path.push(name);
+            }
+            XmlEvent::EndElement { .. } if path.last().map(String::as_str) == Some("entry") => {
+                path.pop();
+                if let Some(track) = entry.take().filter(|track| track.location.is_some()) {
+                    playlist.track_list.tracks.push(track);
+                }
+            }
+            XmlEvent::EndElement { .. } => {
+                path.pop();
+            }
+            XmlEvent::Characters(text) => match (entry.as_mut(), path.last().map(String::as_str)) {
+                (Some(track), Some("title")) => track.title = non_empty(&text),
+                (Some(track), Some("author")) => track.artist = non_empty(&text),
+                (None, Some("title")) => playlist.title = non_empty(&text),
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    Ok(playlist)
+}
+
+fn attribute(attributes: &[OwnedAttribute], name: &str) -> Option<String> {
+    attributes
+        .iter()
+        .find(|attribute| attribute.name.local_name.eq_ignore_ascii_case(name))
+        .and_then(|attribute| non_empty(&attribute.value))
+}
+
+// parses `[[hh:]mm:]ss[.fract]` into milliseconds
+pub fn parse_clock(value: &str) -> Option<i32> {
+    let seconds = value.split(':').try_fold(0.0, |total, part| {
+        part.trim()
+            .parse::<f64>()
+            .map(|part| total * 60.0 + part)
+            .ok()
+    })?;
+    if seconds > 0.0 {
+        Some((seconds * 1_000.0).round() as i32)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn wpl() {
+        let playlist = parse_wpl(
+            r#"<?wpl version="1.0"?>
+<smil>
+  <head><title>Mix</title></head>
+  <body>
+    <seq>
+      <media src="..\Music\01.mp3" trackTitle="Title" trackArtist="Artist" albumTitle="Album" duration="215000"/>
+      <media src="..\Music\02.wma"/>
+      <media trackTitle="Without source"/>
+    </seq>
+  </body>
+</smil>"#,
+        )
+        .unwrap();
+
+        assert_eq!(Some("Mix".to_string()), playlist.title);
+        assert_eq!(
+            vec![
+                Track {
+                    location: Some("..\\Music\\01.mp3".to_string()),
+                    title: Some("Title".to_string()),
+                    artist: Some("Artist".to_string()),
+                    album: Some("Album".to_string()),
+                    duration: Some(215_000),
+                    ..Default::default()
+                },
+                Track {
+                    location: Some("..\\Music\\02.wma".to_string()),
+                    ..Default::default()
+                },
+            ],
+            playlist.track_list.tracks
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn asx() {
+        let playlist = parse_asx(
+            r#"<asx version="3.0">
+  <title>Mix</title>
+  <entry>
+    <title>Title</title>
+    <author>Artist</author>
+    <ref href="http://example.com/01.mp3"/>
+    <ref href="http://example.com/fallback.mp3"/>
+    <duration value="00:03:35.5"/>
+    <param name="Album" value="Album"/>
+  </entry>
+  <entry>
+    <title>Without reference</title>
+  </entry>
+</asx>"#,
+        )
+        .unwrap();
+
+        assert_eq!(Some("Mix".to_string()), playlist.title);
+        assert_eq!(
+            vec![Track {
+                location: Some("http://example.com/01.mp3".to_string()),
+                title: Some("Title".to_string()),
+                artist: Some("Artist".to_string()),
+                album: Some("Album".to_string()),
+                duration: Some(215_500),
+                ..Default::default()
+            }],
+            playlist.track_list.tracks
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn clock() {
+        assert_eq!(Some(215_000), parse_clock("3:35"));
+        assert_eq!(Some(3_723_250), parse_clock("01:02:03.25"));
+        assert_eq!(Some(42_000), parse_clock("42"));
+        assert_eq!(None, parse_clock("0:00"));
+        assert_eq!(None, parse_clock("3:xx"));
+    }
+}
@@ -0,0 +1,471 @@
+use crate::m3u::non_empty;
+use crate::parse_error::ParseError;
+use crate::playlist_types::*;
+
+use xml::common::{Position, TextPosition};
+use xml::escape::{escape_str_attribute, escape_str_pcdata};
+use xml::reader::{EventReader, XmlEvent};
+use xml::writer::{EmitterConfig, EventWriter};
+
+use std::fmt::Display;
+
+// reads the XSPF playlist, tracks with invalid content are skipped and reported
+pub fn parse_xspf(content: &[u8]) -> Result<(Playlist, Vec<ParseError>), ParseError> {
+    let mut reader = EventReader::new(content);
+    let mut playlist = Playlist::default();
+    let mut skipped = Vec::new();
+    let mut path = Vec::<String>::new();
+    let mut text = String::new();
+    let mut rel = String::new();
+    let mut element_position = TextPosition::new();
+    let mut current: Option<(Track, Option<ParseError>)> = None;
+    let mut extension: Option<ExtensionWriter> = None;
+
+    loop {
+        let event = match reader.next() {
+            Ok(event) => event,
+            Err(error) => {
+                let error = ParseError::from_xml(&error, path.last().map(String::as_str));
+                if playlist.track_list.tracks.is_empty() {
+                    return Err(error);
+                }
+                // keep the tracks that were read before the error
+                skipped.push(error);
+                break;
+            }
+        };
+
+        // the content of extensions is copied until the closing tag
+        if let Some(writer) = extension.as_mut() {
+            match &event {
+                XmlEvent::EndElement { .. } if path.len() == writer.depth => {}
+                _ => {
+                    match &event {
+                        XmlEvent::StartElement { name, .. } => path.push(name.local_name.clone()),
+                        XmlEvent::EndElement { .. } => {
+                            path.pop();
+                        }
+                        _ => {}
+                    }
+                    if let Some(event) = event.as_writer_event() {
+                        if let Err(error) = writer.writer.write(event) {
+                            writer.error.get_or_insert(error.to_string());
+                        }
+                    }
+                    continue;
+                }
+            }
+        }
+
+        match event {
+            XmlEvent::StartElement {
+                name, attributes, ..
+            } => {
+                if path.is_empty() && name.local_name != "playlist" {
+                    return Err(ParseError::new("expected a <playlist> root element")
+                        .at(reader.position())
+                        .in_element(Some(&name.local_name)));
+                }
+                let parent = path.last().map(String::as_str);
+                if name.local_name == "track" && parent == Some("trackList") {
+                    current = Some((Track::default(), None));
+                }
+                rel = attributes
+                    .into_iter()
+                    .find(|attribute| {
+                        attribute.name.local_name == "rel"
+                            || attribute.name.local_name == "application"
+                    })
+                    .map(|attribute| attribute.value)
+                    .unwrap_or_default();
+                if name.local_name == "extension"
+                    && (parent == Some("playlist") || parent == Some("track"))
+                {
+                    extension = Some(ExtensionWriter {
+                        depth: path.len() + 1,
+                        application: rel.clone(),
+                        writer: EmitterConfig::new()
+                            .write_document_declaration(false)
+                            .create_writer(Vec::new()),
+                        position: reader.position(),
+                        error: None,
+                    });
+                }
+                element_position = reader.position();
+                text.clear();
+                path.push(name.local_name);
+            }
+            XmlEvent::Characters(characters) | XmlEvent::CData(characters) => {
+                text.push_str(&characters);
+            }
+            XmlEvent::EndElement { .. } => {
+                let element = path.pop().unwrap_or_default();
+                let parent = path.last().map(String::as_str);
+                match (parent, element.as_str(), current.as_mut()) {
+                    (Some("track"), _, Some((track, error))) => {
+                        let value = non_empty(&text);
+                        match element.as_str() {
+                            "location" => track.location = track.location.take().or(value),
+                            "identifier" => track.identifier = track.identifier.take().or(value),
+                            "title" => track.title = value,
+                            "creator" => track.artist = value,
+                            "annotation" => track.annotation = value,
+                            "info" => track.info = value,
+                            "image" => track.image = value,
+                            "album" => track.album = value,
+                            "trackNum" | "duration" => {
+                                match value.map(|value| value.parse::<i32>()).transpose() {
+                                    Ok(number) if element == "trackNum" => {
+                                        track.track_number = number
+                                    }
+                                    Ok(number) => track.duration = number,
+                                    Err(_) => {
+                                        error.get_or_insert_with(|| {
+                                            ParseError::new(format!(
+                                                "invalid number \"{}\"",
+                                                text.trim()
+                                            ))
+                                            .at(element_position)
+                                            .in_element(Some(&element))
+                                        });
+                                    }
+                                }
+                            }
+                            "link" => track.links.push(XspfLink {
+                                rel: std::mem::take(&mut rel),
+                                content: text.trim().to_owned(),
+                            }),
+                            "meta" => track.meta.push(XspfMeta {
+                                rel: std::mem::take(&mut rel),
+                                content: text.trim().to_owned(),
+                            }),
+                            "extension" => match extension.take().map(ExtensionWriter::finish) {
+                                Some(Ok(extension)) => track.extensions.push(extension),
+                                Some(Err(error)) => skipped.push(error),
+                                None => {}
+                            },
+                            _ => {}
+                        }
+                    }
+                    (Some("trackList"), "track", _) => match current.take() {
+                        Some((mut track, None)) => {
+                            track.read_known_meta();
+                            playlist.track_list.tracks.push(track);
+                        }
+                        Some((track, Some(mut error))) => {
+                            error.message = format!(
+                                "skipped track \"{}\", {}",
+                                track.title.as_deref().unwrap_or("untitled"),
+                                error.message
+                            );
+                            skipped.push(error);
+                        }
+                        None => {}
+                    },
+                    (Some("playlist"), _, _) => {
+                        let value = non_empty(&text);
+                        match element.as_str() {
+                            "title" => playlist.title = value,
+                            "creator" => playlist.creator = value,
+                            "annotation" => playlist.annotation = value,
+                            "info" => playlist.info = value,
+                            "location" => playlist.location = value,
+                            "identifier" => playlist.identifier = value,
+                            "image" => playlist.image = value,
+                            "date" => playlist.date = value,
+                            "license" => playlist.license = value,
+                            "link" => playlist.links.push(XspfLink {
+                                rel: std::mem::take(&mut rel),
+                                content: text.trim().to_owned(),
+                            }),
+                            "meta" => playlist.meta.push(XspfMeta {
+                                rel: std::mem::take(&mut rel),
+                                content: text.trim().to_owned(),
+                            }),
+                            "extension" => match extension.take().map(ExtensionWriter::finish) {
+                                Some(Ok(extension)) => playlist.extensions.push(extension),
+                                Some(Err(error)) => skipped.push(error),
+                                None => {}
+                            },
+                            _ => {}
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            XmlEvent::EndDocument => break,
+            _ => {}
+        }
+    }
+
+    Ok((playlist, skipped))
+}
+
+struct ExtensionWriter {
+    depth: usize,
+    application: String,
+    writer: EventWriter<Vec<u8>>,
+    position: TextPosition,
+    // the first event that couldn't be copied
+    error: Option<String>,
+}
+
+impl ExtensionWriter {
+    // an extension that couldn't be copied completely is skipped rather than kept truncated
+    fn finish(self) -> Result<XspfExtension, ParseError> {
+        match self.error {
+            Some(error) => Err(ParseError::new(format!(
+                "skipped extension \"{}\", {}",
+                self.application, error
+            ))
+            .at(self.position)
+            .in_element(Some("extension"))),
+            None => Ok(XspfExtension {
+                application: self.application,
+                content: String::from_utf8_lossy(&self.writer.into_inner()).into_owned(),
+            }),
+        }
+    }
+}
+
+pub fn write_xspf(playlist: &Playlist) -> String {
+    let mut lines = vec![
+        r#"<?xml version="1.0" encoding="UTF-8"?>"#.to_owned(),
+        r#"<playlist version="1" xmlns="http://xspf.org/ns/0/">"#.to_owned(),
+    ];
+    write_element(&mut lines, 1, "title", playlist.title.as_ref());
+    write_element(&mut lines, 1, "creator", playlist.creator.as_ref());
+    write_element(&mut lines, 1, "annotation", playlist.annotation.as_ref());
+    write_element(&mut lines, 1, "info", playlist.info.as_ref());
+    write_element(&mut lines, 1, "location", playlist.location.as_ref());
+    write_element(&mut lines, 1, "identifier", playlist.identifier.as_ref());
+    write_element(&mut lines, 1, "image", playlist.image.as_ref());
+    write_element(&mut lines, 1, "date", playlist.date.as_ref());
+    write_element(&mut lines, 1, "license", playlist.license.as_ref());
+    write_relations(
+        &mut lines,
+        1,
+        &playlist.links,
+        &playlist.meta,
+        &playlist.extensions,
+    );
+
+    lines.push("  <trackList>".to_owned());
+    for track in playlist.track_list.tracks.iter() {
+        lines.push("    <track>".to_owned());
+        write_element(&mut lines, 3, "location", track.location.as_ref());
+        write_element(&mut lines, 3, "identifier", track.identifier.as_ref());
+        write_element(&mut lines, 3, "title", track.title.as_ref());
+        write_element(&mut lines, 3, "creator", track.artist.as_ref());
+        write_element(&mut lines, 3, "annotation", track.annotation.as_ref());
+        write_element(&mut lines, 3, "info", track.info.as_ref());
+        write_element(&mut lines, 3, "image", track.image.as_ref());
+        write_element(&mut lines, 3, "album", track.album.as_ref());
+        write_element(&mut lines, 3, "trackNum", track.track_number.as_ref());
+        write_element(&mut lines, 3, "duration", track.duration.as_ref());
+        write_relations(&mut lines, 3, &track.links, &track.meta, &track.extensions);
+        lines.push("    </track>".to_owned());
+    }
+    lines.push("  </trackList>".to_owned());
+    lines.push("</playlist>".to_owned());
+
+    lines.join("\n")
+}
+
+fn write_element(lines: &mut Vec<String>, depth: usize, name: &str, value: Option<impl Display>) {
+    if let Some(value) = value {
+        lines.push(format!(
+            "{}<{}>{}</{}>",
+            "  ".repeat(depth),
+            name,
+            escape_str_pcdata(&value.to_string()),
+            name
+        ));
+    }
+}
+
+fn write_relations(
+    lines: &mut Vec<String>,
+    depth: usize,
+    links: &[XspfLink],
+    meta: &[XspfMeta],
+    extensions: &[XspfExtension],
+) {
+    let indent = "  ".repeat(depth);
+    let relations = links
+        .iter()
+        .map(|link| ("link", &link.rel, &link.content))
+        .chain(meta.iter().map(|meta| ("meta", &meta.rel, &meta.content)));
+    for (name, rel, content) in relations {
+        lines.push(format!(
+            "{}<{} rel=\"{}\">{}</{}>",
+            indent,
+            name,
+            escape_str_attribute(rel),
+            escape_str_pcdata(content),
+            name
+        ));
+    }
+    // extensions are written back unchanged
+    for extension in extensions {
+        lines.push(format!(
+            "{}<extension application=\"{}\">{}</extension>",
+            indent,
+            escape_str_attribute(&extension.application),
+            extension.content
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn skip_invalid_tracks() {
+        let (playlist, skipped) = parse_xspf(
+            br#"<?xml version="1.0" encoding="UTF-8"?>
+<playlist version="1" xmlns="http://xspf.org/ns/0/">
+  <trackList>
+    <track><title>Valid</title><duration>1000</duration></track>
+    <track><title>Invalid</title><duration>1:00</duration></track>
+  </trackList>
+</playlist>"#,
+        )
+        .unwrap();
+
+        assert_eq!(1, playlist.track_list.tracks.len());
+        assert_eq!(1, skipped.len());
+        assert_eq!(Some(5), skipped[0].line);
+        assert_eq!(Some("duration".to_string()), skipped[0].element);
+    }
+
+    #[wasm_bindgen_test]
+    fn locate_syntax_error() {
+        let error = parse_xspf(
+            br#"<playlist version="1" xmlns="http://xspf.org/ns/0/">
+  <trackList>
+    <track><title>Broken</titel></track>"#,
+        )
+        .unwrap_err();
+
+        assert_eq!(Some(3), error.line);
+        assert_eq!(Some("title".to_string()), error.element);
+    }
+
+    #[wasm_bindgen_test]
+    fn write_round_trip() {
+        let playlist = Playlist {
+            title: Some("Mix <2020>".to_string()),
+            creator: Some("Tom & Jerry".to_string()),
+            annotation: Some("\"Quotes\" & 'apostrophes'".to_string()),
+            date: Some("2020-01-01T00:00:00Z".to_string()),
+            track_list: TrackList {
+                tracks: vec![
+                    Track {
+                        location: Some("file:///music/a&b.mp3?x=1&y=2".to_string()),
+                        title: Some("The Boxer".to_string()),
+                        artist: Some("Simon & Garfunkel".to_string()),
+                        album: Some("Bridge <Over> Troubled Water".to_string()),
+                        track_number: Some(3),
+                        duration: Some(308_000),
+                        ..Default::default()
+                    },
+                    Track {
+                        title: Some("Untitled".to_string()),
+                        ..Default::default()
+                    },
+                ],
+            },
+            ..Default::default()
+        };
+        let written = write_xspf(&playlist);
+
+        assert_eq!(
+            playlist,
+            serde_xml_rs::from_str::<Playlist>(&written).unwrap()
+        );
+        let (parsed, skipped) = parse_xspf(written.as_bytes()).unwrap();
+        assert!(skipped.is_empty());
+        assert_eq!(playlist.title, parsed.title);
+        assert_eq!(playlist.track_list, parsed.track_list);
+    }
+
+    #[wasm_bindgen_test]
+    fn write_relations() {
+        let playlist = Playlist {
+            meta: vec![XspfMeta {
+                rel: "http://example.com/rel?a&b".to_string(),
+                content: "1 < 2".to_string(),
+            }],
+            track_list: TrackList {
+                tracks: vec![Track {
+                    links: vec![XspfLink {
+                        rel: "http://example.com/link".to_string(),
+                        content: "http://example.com/?a=1&b=2".to_string(),
+                    }],
+                    extensions: vec![XspfExtension {
+                        application: "http://example.com/app".to_string(),
+                        content: "<app:rating xmlns:app=\"http://example.com/app\">5</app:rating>"
+                            .to_string(),
+                    }],
+                    ..Default::default()
+                }],
+            },
+            ..Default::default()
+        };
+        let written = write_xspf(&playlist);
+
+        assert!(written.contains(r#"  <meta rel="http://example.com/rel?a&amp;b">1 &lt; 2</meta>"#));
+        assert!(written.contains(
+            r#"      <link rel="http://example.com/link">http://example.com/?a=1&amp;b=2</link>"#
+        ));
+        assert!(written.contains(r#"<extension application="http://example.com/app"><app:rating xmlns:app="http://example.com/app">5</app:rating></extension>"#));
+        // the document stays well-formed
+        parse_xspf(written.as_bytes()).unwrap();
+    }
+
+    #[wasm_bindgen_test]
+    fn preserve_relations() {
+        let (playlist, _) = parse_xspf(
+            br#"<?xml version="1.0" encoding="UTF-8"?>
+<playlist version="1" xmlns="http://xspf.org/ns/0/" xmlns:app="http://example.com/app">
+  <title>Mix</title>
+  <creator>Somebody</creator>
+  <link rel="http://example.com/homepage">http://example.com/?a=1&amp;b=2</link>
+  <extension application="http://example.com/app"><app:owner id="1">Somebody</app:owner></extension>
+  <trackList>
+    <track>
+      <title>Title</title>
+      <image>http://example.com/cover.jpg</image>
+      <meta rel="http://example.com/ns/isrc">USRC17607839</meta>
+      <extension application="http://example.com/app">
+        <app:rating>5</app:rating>
+      </extension>
+    </track>
+  </trackList>
+</playlist>"#,
+        )
+        .unwrap();
+
+        assert_eq!(Some("Somebody".to_string()), playlist.creator);
+        assert_eq!("http://example.com/?a=1&b=2", playlist.links[0].content);
+        assert_eq!(1, playlist.extensions.len());
+        assert!(playlist.extensions[0]
+            .content
+            .contains("Somebody</app:owner>"));
+        let track = &playlist.track_list.tracks[0];
+        assert_eq!(
+            Some("http://example.com/cover.jpg".to_string()),
+            track.image
+        );
+        assert_eq!(Some("USRC17607839".to_string()), track.isrc);
+        assert_eq!("http://example.com/app", track.extensions[0].application);
+        assert!(track.extensions[0].content.contains("5</app:rating>"));
+
+        let (written, skipped) = parse_xspf(write_xspf(&playlist).as_bytes()).unwrap();
+        assert!(skipped.is_empty());
+        assert_eq!(playlist, written);
+    }
+}